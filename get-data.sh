@@ -34,6 +34,23 @@ then
     rm WordNet-3.0-glosstag.tar.gz
 fi
 
+if [ ! -f data/dict/noun.exc ]
+then
+    curl http://wordnetcode.princeton.edu/3.0/WNdb-3.0.tar.gz -o WNdb-3.0.tar.gz
+    mkdir -p data/dict
+    tar xzvf WNdb-3.0.tar.gz -C data/dict --strip-components=1 \
+        dict/noun.exc dict/verb.exc dict/adj.exc dict/adv.exc
+    rm WNdb-3.0.tar.gz
+fi
+
+if [ ! -f data/ic-brown.dat ]
+then
+    curl -L https://raw.githubusercontent.com/nltk/nltk_data/gh-pages/packages/corpora/wordnet_ic.zip \
+        -o wordnet_ic.zip
+    unzip -j wordnet_ic.zip 'wordnet_ic/ic-*.dat' -d data
+    rm wordnet_ic.zip
+fi
+
 if [ ! -d data/wns ]
 then
     curl http://compling.hss.ntu.edu.sg/omw/all.zip -o all.zip
@@ -57,3 +74,8 @@ if [ ! -f data/ili-map-dbpedia.ttl ]
 then
     curl http://john.mccr.ae/ili-map-dbpedia.ttl -o data/ili-map-dbpedia.ttl
 fi
+
+if [ ! -f data/ili-map-wikidata.ttl ]
+then
+    curl http://john.mccr.ae/ili-map-wikidata.ttl -o data/ili-map-wikidata.ttl
+fi
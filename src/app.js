@@ -27,10 +27,17 @@ angular.module('app').controller('SearchController',
     self.results = [];
     self.query_cleared = false;
     self.query = '';
+    self.schemes = [];
+    $http.get("/json/schemes", { cache: true }).then(
+        function(result) {
+            self.schemes = result.data;
+        }, function(response) {
+            console.log(response.data);
+        });
     var m = $location.path().match("/(.*)/(.*)");
     self.selectedItemChange = function(item) {
         if(item) {
-            $location.path("/"+ self.index + "/"+item.item);
+            $location.path("/"+ self.index.replace(/^lemma-/, "lemma/") + "/"+item.item);
             $scope.$parent.focus = item.item;
             self.results = [];
             self.query_cleared = true;
@@ -41,6 +48,18 @@ angular.module('app').controller('SearchController',
                 }, function(response) {
                     console.log(response.data);
                 });
+            $scope.$parent.suggestions = [];
+            $scope.$parent.base_forms = [];
+            if(self.index == "lemma" || self.index.startsWith("lemma-")) {
+                var suggestLang = self.index == "lemma" ? "en" : self.index.substring(6);
+                $http.get("/json/suggest/" + suggestLang + "/" + item.item).then(
+                    function(result) {
+                        $scope.$parent.suggestions = result.data.suggestions;
+                        $scope.$parent.base_forms = result.data.base_forms;
+                    }, function(response) {
+                        console.log(response.data);
+                    });
+            }
         } else {
             $scope.$parent.focus = "";
             $scope.$parent.synsets = [];
@@ -49,7 +68,7 @@ angular.module('app').controller('SearchController',
     };
 
     if(m) {
-        self.index = m[1];
+        self.index = m[1].replace(/^lemma\//, "lemma-");
         if(m[2].endsWith(".html")) {
             self.selectedItem = { item: m[2].substring(0, m[2].length - 5), display: m[2] };
         } else {
@@ -107,6 +126,25 @@ angular.module('app').component('synset', {
                         ctrl.targetsynsetsextra = response.data.slice(maxEntriesToLoad,response.data.length);
                     }, function(response) { /*alert(response);*/ }
             );
+            ctrl.oldIds = [];
+            $http.get("/json/schemes", { cache: true }).then(
+                    function(response) {
+                        ctrl.oldIds = [];
+                        response.data.forEach((scheme) => {
+                            ((ctrl.synset.old_keys || {})[scheme.name] || []).forEach((id) => {
+                                ctrl.oldIds.push({ scheme: scheme, id: id });
+                            });
+                        });
+                    }, function(response) { /*alert(response);*/ }
+            );
+            ctrl.verbnet = [];
+            if(this.synset.links && this.synset.links.some((link) => link.link_type === 'VerbNet')) {
+                $http.get("/json/verbnet_classes/" + this.synset.id).then(
+                        function(response) {
+                            ctrl.verbnet = response.data;
+                        }, function(response) { /*alert(response);*/ }
+                );
+            }
             ctrl.hasSubcats = function() {
                 for(i = 0; i < this.synset.lemmas.length; i++) {
                     if(this.synset.lemmas[i].subcats.length > 0) {
@@ -0,0 +1,69 @@
+//! Statistics on how completely each language covers the synsets of the
+//! wordnet
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead,BufReader};
+use crate::wordnet::{WNKey, WordNetLoadError};
+
+/// The file that lists the core concepts, one synset identifier per line
+pub const CORE_SYNSETS_FILE : &str = "data/core-synsets.txt";
+
+/// The coverage of all the languages in the database
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct Coverage {
+    pub synsets : u32,
+    /// The number of core concepts, if a list of them was given
+    pub core : Option<u32>,
+    pub languages : Vec<LanguageCoverage>
+}
+
+/// The coverage of one language
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct LanguageCoverage {
+    pub language : String,
+    /// The number of synsets with at least one lemma in this language
+    pub synsets : u32,
+    /// The proportion of all synsets that are covered
+    pub coverage : f64,
+    /// The number of distinct lemmas for each part of speech
+    pub lemmas : BTreeMap<String, u32>,
+    /// The number of senses (lemma-synset pairs) for each part of speech
+    pub senses : BTreeMap<String, u32>,
+    /// The proportion of the core concepts that are covered
+    pub core_coverage : Option<f64>,
+    /// The synsets without lemmas in this language that have the most
+    /// hyponyms
+    pub uncovered : Vec<UncoveredSynset>
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct UncoveredSynset {
+    pub id : WNKey,
+    pub hyponyms : u32
+}
+
+/// Read the list of core concepts
+pub fn read_core_synsets<P : AsRef<Path>>(path : P) -> Result<Vec<WNKey>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.starts_with("#") && !line.is_empty() {
+            result.push(line.to_string());
+        }
+    }
+    result.sort();
+    result.dedup();
+    Ok(result)
+}
+
+/// The core concepts, if the file listing them exists
+pub fn core_synsets() -> Result<Option<Vec<WNKey>>, WordNetLoadError> {
+    if Path::new(CORE_SYNSETS_FILE).exists() {
+        Ok(Some(read_core_synsets(CORE_SYNSETS_FILE)?))
+    } else {
+        Ok(None)
+    }
+}
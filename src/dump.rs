@@ -14,20 +14,42 @@ mod wordnet;
 mod wordnet_model;
 mod omwn;
 mod links;
+mod fuzzy;
+mod morphy;
+mod normalize;
+mod pronunciation;
+mod taxonomy;
+mod similarity;
+mod relations;
+mod schemes;
+mod translate;
+mod coverage;
+mod verbnet;
 mod glosstag;
 mod wordnet_read;
 
 use crate::wordnet::{WNKey,WordNet};
 use crate::wordnet_model::Synset;
-use std::collections::HashMap;
+use crate::glosstag::{annotated_glosses, GlossText};
+use std::collections::{BTreeSet,HashMap};
 use clap::{App,Arg};
 use handlebars::{Handlebars};
 use std::str::FromStr;
+use std::io::{self, BufRead};
+use std::process::exit;
 
 
+/// A synset with its tagged glosses, for the RDF templates
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct SynsetHB {
+    #[serde(flatten)]
+    synset : Synset,
+    glosses : Vec<GlossText>
+}
+
 #[derive(Clone,Debug,Serialize,Deserialize)]
 struct SynsetsHB {
-    synsets : Vec<Synset>,
+    synsets : Vec<SynsetHB>,
     entries : HashMap<String, Vec<Synset>>,
     index : String,
     name : String,
@@ -98,7 +120,10 @@ fn make_synsets_hb(synsets : Vec<Synset>, index : &str,
         WordNetSite::Polylingual => "http://polylingwn.linguistic-lod.org"
     };
     SynsetsHB {
-        synsets: synsets,
+        synsets: synsets.into_iter().map(|synset| SynsetHB {
+            glosses: annotated_glosses(&synset.gloss),
+            synset
+        }).collect(),
         entries: entries,
         index : index.to_owned(),
         name: name.to_owned(),
@@ -156,6 +181,71 @@ enum WordNetSite {
 
 
 
+/// Map the identifiers in the first column of a TSV file read from standard
+/// input. Each line is written with the mapped identifiers (space-separated
+/// if there are several) inserted as the second column, and the keys that
+/// could not be mapped or mapped to several identifiers are reported
+fn map_tsv(wordnet : &WordNet, from : &str, to : &str) {
+    let stdin = io::stdin();
+    let lines : Vec<String> = stdin.lock().lines()
+        .collect::<Result<Vec<String>, io::Error>>()
+        .expect("Could not read input");
+    let keys : Vec<String> = lines.iter()
+        .map(|l| l.split("\t").next().unwrap_or("").to_string())
+        .collect();
+    let mappings = wordnet.map_ids(from, to, &keys)
+        .expect("Could not map identifiers");
+    let mut unmapped = 0;
+    let mut one_to_many = 0;
+    for (line, mapping) in lines.iter().zip(mappings.iter()) {
+        if mapping.targets.is_empty() {
+            unmapped += 1;
+        } else if mapping.one_to_many {
+            one_to_many += 1;
+        }
+        match line.find("\t") {
+            Some(i) => println!("{}\t{}{}", mapping.key, mapping.targets.join(" "), &line[i..]),
+            None => println!("{}\t{}", mapping.key, mapping.targets.join(" "))
+        }
+    }
+    eprintln!("Mapped {} identifiers ({} not mapped, {} mapped to several identifiers)",
+              mappings.len(), unmapped, one_to_many);
+}
+
+/// Print the coverage of each language as a table, followed by the
+/// uncovered synsets with the most hyponyms for each language
+fn coverage_report(wordnet : &WordNet) {
+    let core = coverage::core_synsets()
+        .expect("Could not load core synsets");
+    let result = wordnet.coverage(core.as_deref(), 10)
+        .expect("Could not compute coverage");
+    let pos : Vec<String> = result.languages.iter()
+        .flat_map(|l| l.lemmas.keys().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter().collect();
+    print!("language\tsynsets\tcoverage\tcore");
+    for p in pos.iter() {
+        print!("\tlemmas-{}\tsenses-{}", p, p);
+    }
+    println!();
+    for l in result.languages.iter() {
+        print!("{}\t{}\t{:.3}\t{}", l.language, l.synsets, l.coverage,
+               l.core_coverage.map(|c| format!("{:.3}", c)).unwrap_or("-".to_string()));
+        for p in pos.iter() {
+            print!("\t{}\t{}", l.lemmas.get(p).unwrap_or(&0), l.senses.get(p).unwrap_or(&0));
+        }
+        println!();
+    }
+    println!();
+    println!("language\tuncovered\thyponyms");
+    for l in result.languages.iter() {
+        for u in l.uncovered.iter() {
+            println!("{}\t{}\t{}", l.language, u.id, u.hyponyms);
+        }
+    }
+    eprintln!("{} synsets, {} languages", result.synsets, result.languages.len());
+}
+
 fn main() {
     let app = App::new("wordnet-rdf-dump")
         .version("1.0")
@@ -175,7 +265,25 @@ fn main() {
             .long("wn")
             .value_name("wn31.xml")
             .help("The WordNet file in GWC LMF-XML format, e.g., http://john.mccr.ae/wn31.xml. Default is data/wn31.xml. If specified the dump program will first load this database")
-            .takes_value(true));
+            .takes_value(true))
+        .arg(Arg::with_name("glosstags")
+            .long("glosstags")
+            .value_name("data/merged")
+            .help("The directory of the Princeton gloss tag corpus to load with --wn")
+            .takes_value(true))
+        .arg(Arg::with_name("map-from")
+            .long("map-from")
+            .value_name("pwn20")
+            .help("Instead of dumping, map the identifiers in the first column of a TSV file read from standard input from this scheme (id, ili, sense_key or pwnXX)")
+            .takes_value(true))
+        .arg(Arg::with_name("map-to")
+            .long("map-to")
+            .value_name("pwn30")
+            .help("The scheme to map identifiers to with --map-from")
+            .takes_value(true))
+        .arg(Arg::with_name("coverage")
+            .long("coverage")
+            .help("Instead of dumping, report the coverage of the synsets by each language"));
 
     let matches = app.clone().get_matches();
     let site = match matches.value_of("site").unwrap_or("princeton") {
@@ -188,19 +296,40 @@ fn main() {
         Some(wn_file) => {
             eprintln!("Loading WordNet data");
             if site == WordNetSite::Princeton {
-                wordnet_read::load_pwn(wn_file)
+                wordnet_read::load_pwn(wn_file, matches.value_of("glosstags"))
                     .expect("Failed to load WordNet");
             } else if site == WordNetSite::English {
-                wordnet_read::load_enwn(wn_file)
+                wordnet_read::load_enwn(wn_file, matches.value_of("glosstags"))
                     .expect("Failed to load WordNet");
             } else {
-                wordnet_read::load_gwn(wn_file)
+                wordnet_read::load_gwn(wn_file, matches.value_of("glosstags"))
                     .expect("Failed to load WordNet");
             }
         },
         None => {}
     };
     let wordnet = wordnet::WordNet::new();
+    match (matches.value_of("map-from"), matches.value_of("map-to")) {
+        (Some(from), Some(to)) => {
+            for scheme in [from, to].iter() {
+                if !wordnet.is_id_scheme(scheme).expect("DB error") {
+                    eprintln!("Unknown identifier scheme: {}", scheme);
+                    exit(1);
+                }
+            }
+            map_tsv(&wordnet, from, to);
+            return;
+        },
+        (None, None) => {},
+        _ => {
+            eprintln!("Both --map-from and --map-to must be given");
+            exit(1);
+        }
+    }
+    if matches.is_present("coverage") {
+        coverage_report(&wordnet);
+        return;
+    }
     let mut handlebars = Handlebars::new();
     handlebars.register_template_string("ttl", include_str!("ttl-dump.hbs"))
         .expect("Could not load ttl.hbs");
@@ -220,7 +349,10 @@ fn main() {
 @prefix synsem: <http://www.w3.org/ns/lemon/synsem#> .
 @prefix wn: <https://globalwordnet.github.io/schemas/wn#> .
 @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
-@prefix dct: <http://purl.org/dc/terms/> .");
+@prefix dct: <http://purl.org/dc/terms/> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix nif: <http://persistence.uni-leipzig.org/nlp2rdf/ontologies/nif-core#> .
+@prefix itsrdf: <http://www.w3.org/2005/11/its/rdf#> .");
     if site == WordNetSite::Princeton {
         println!("@prefix wordnetlicense: <http://wordnet.princeton.edu/wordnet/license/> .
 @prefix wnlemma: <http://wordnet-rdf.princeton.edu/rdf/lemma/> .
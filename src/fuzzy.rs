@@ -0,0 +1,84 @@
+//! Approximate matching of lemmas, used to suggest a lemma when a lookup
+//! does not find anything
+use std::collections::HashSet;
+
+/// The trigrams of a (lower-cased) form. The form is padded with spaces so
+/// that the start and end of the word also contribute trigrams.
+pub fn trigrams(form : &str) -> Vec<String> {
+    let padded : Vec<char> = format!("  {} ", form).chars().collect();
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for w in padded.windows(3) {
+        let t : String = w.iter().collect();
+        if seen.insert(t.clone()) {
+            result.push(t);
+        }
+    }
+    result
+}
+
+/// The edit distance between two strings, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters (so that
+/// "recieve" is one edit from "receive")
+pub fn edit_distance(s : &str, t : &str) -> usize {
+    let s : Vec<char> = s.chars().collect();
+    let t : Vec<char> = t.chars().collect();
+    let mut d = vec![vec![0usize; t.len() + 1]; s.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=s.len() {
+        for j in 1..=t.len() {
+            let cost = if s[i-1] == t[j-1] { 0 } else { 1 };
+            d[i][j] = (d[i-1][j] + 1)
+                .min(d[i][j-1] + 1)
+                .min(d[i-1][j-1] + cost);
+            if i > 1 && j > 1 && s[i-1] == t[j-2] && s[i-2] == t[j-1] {
+                d[i][j] = d[i][j].min(d[i-2][j-2] + 1);
+            }
+        }
+    }
+    d[s.len()][t.len()]
+}
+
+/// The number of lemmas sharing the most trigrams with a query that are
+/// ranked by edit distance
+pub const MAX_CANDIDATES : u32 = 2000;
+
+/// The largest edit distance at which a form is still offered as a
+/// suggestion for a query
+pub fn max_distance(query : &str) -> usize {
+    (query.chars().count() / 3).clamp(1, 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigrams() {
+        assert_eq!(trigrams("cat"), vec!["  c", " ca", "cat", "at "]);
+        assert_eq!(trigrams("aaaa"), vec!["  a", " aa", "aaa", "aa "]);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("cat", "cat"), 0);
+        assert_eq!(edit_distance("cat", "cart"), 1);
+        assert_eq!(edit_distance("cart", "cat"), 1);
+        assert_eq!(edit_distance("cat", "cut"), 1);
+        assert_eq!(edit_distance("recieve", "receive"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "dog"), 3);
+    }
+
+    #[test]
+    fn test_max_distance() {
+        assert_eq!(max_distance("ox"), 1);
+        assert_eq!(max_distance("receive"), 2);
+        assert_eq!(max_distance("internationalization"), 3);
+    }
+}
@@ -1,20 +1,23 @@
 use std::fs::File;
 use std::path::Path;
-use std::io::{BufReader};
+use std::io::{BufRead,BufReader};
 use xml::reader::{EventReader, XmlEvent};
 use xml::attribute::OwnedAttribute;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::fmt;
 use crate::wordnet::{WNKey, WordNetLoadError, WordNetBuilder};
+use crate::schemes::scheme_path;
 
 #[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct GlossTagWord {
-    text : String,
-    lemma : Option<String>,
-    sep : String,
-    pos : Option<String>,
-    tag : Option<String>,
-    synset : Option<String>,
-    glob : Option<String>
+    pub text : String,
+    pub lemma : Option<String>,
+    pub sep : String,
+    pub pos : Option<String>,
+    pub tag : Option<String>,
+    pub synset : Option<String>,
+    pub glob : Option<String>
 }
 
 #[derive(Clone,Debug,Serialize,Deserialize)]
@@ -22,26 +25,176 @@ pub enum GlossType {
     Aux, Def, Ex
 }
 
+impl fmt::Display for GlossType {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            GlossType::Aux => "Aux",
+            GlossType::Def => "Def",
+            GlossType::Ex => "Ex"
+        })
+    }
+}
+
+impl FromStr for GlossType {
+    type Err = WordNetLoadError;
+    fn from_str(s : &str) -> Result<GlossType, WordNetLoadError> {
+        match s {
+            "Aux" => Ok(GlossType::Aux),
+            "Def" => Ok(GlossType::Def),
+            "Ex" => Ok(GlossType::Ex),
+            _ => Err(WordNetLoadError::Schema("Bad gloss type"))
+        }
+    }
+}
+
 #[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct Gloss {
-    words : Vec<GlossTagWord>,
-    gloss_type : GlossType
+    pub words : Vec<GlossTagWord>,
+    pub gloss_type : GlossType
+}
+
+impl Gloss {
+    /// The text of the gloss
+    pub fn text(&self) -> String {
+        let text : String = self.words.iter()
+            .map(|w| format!("{}{}", w.text, w.sep))
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    /// The start and end (in characters) of each word in the text
+    pub fn offsets(&self) -> Vec<(usize, usize)> {
+        let mut start = 0;
+        self.words.iter().map(|w| {
+            let len = w.text.chars().count();
+            let offset = (start, start + len);
+            start += len + w.sep.chars().count();
+            offset
+        }).collect()
+    }
+}
+
+/// A gloss as text with the offsets of the words that are tagged with
+/// synsets, as published in the RDF output
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct GlossText {
+    /// The position of the gloss among the glosses of the synset
+    pub index : usize,
+    pub gloss_type : GlossType,
+    pub text : String,
+    pub annotations : Vec<GlossAnnotation>
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct GlossAnnotation {
+    pub word : String,
+    pub start : usize,
+    pub end : usize,
+    pub synset : WNKey
+}
+
+/// The glosses of a synset that have at least one tagged word
+pub fn annotated_glosses(glosses : &Option<Vec<Gloss>>) -> Vec<GlossText> {
+    glosses.iter().flatten().enumerate().filter_map(|(index, gloss)| {
+        let annotations : Vec<GlossAnnotation> = gloss.words.iter()
+            .zip(gloss.offsets())
+            .flat_map(|(word, (start, end))| {
+                word.synset.iter().chain(word.glob.iter()).map(move |synset| GlossAnnotation {
+                    word: word.text.clone(),
+                    start,
+                    end,
+                    synset: synset.clone()
+                })
+            })
+            .collect();
+        if annotations.is_empty() {
+            None
+        } else {
+            Some(GlossText {
+                index,
+                gloss_type: gloss.gloss_type.clone(),
+                text: gloss.text(),
+                annotations
+            })
+        }
+    }).collect()
+}
+
+/// A use of a synset in the gloss of another synset, that is a word in the
+/// gloss that is tagged with the synset
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct GlossUse {
+    /// The synset whose gloss uses the synset
+    pub synset : WNKey,
+    pub gloss_type : GlossType,
+    pub word : String,
+    pub start : u32,
+    pub end : u32,
+    /// The text of the gloss
+    pub context : String
 }
 
 pub type GlossTagCorpus = HashMap<WNKey, Vec<Gloss>>;
 
+/// The glosses and sense references of the corpus that could not be mapped
+/// to the wordnet
+#[derive(Default)]
+struct Unmapped {
+    glosses : Vec<String>,
+    senses : usize
+}
+
+/// Read the WordNet 3.0 keys and ILIs of the ILI mapping
+fn read_ili_map<P : AsRef<Path>>(path : P) -> Result<HashMap<WNKey, String>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = HashMap::new();
+    for line in file.lines() {
+        let line = line?;
+        let mut elems = line.split("\t");
+        if let (Some(ili), Some(key)) = (elems.next(), elems.next()) {
+            result.insert(key.to_string(), ili.to_string());
+        }
+    }
+    Ok(result)
+}
+
+/// Find the synset of the corpus by its WordNet 3.0 key or, if the WordNet
+/// 3.0 index is not loaded, by its ILI or its sense keys
+fn resolve_synset(wordnet : &WordNetBuilder, wn30 : &WNKey, sense_keys : &[String],
+                  ili_map : &HashMap<WNKey, String>) -> Result<Option<WNKey>, WordNetLoadError> {
+    if let Some(id) = wordnet.get_id_by_pwn30(wn30)? {
+        return Ok(Some(id));
+    }
+    if let Some(ili) = ili_map.get(wn30) {
+        if let Some(id) = wordnet.get_id_by_ili(ili)? {
+            return Ok(Some(id));
+        }
+    }
+    for sk in sense_keys.iter() {
+        if let Some(id) = wordnet.get_id_by_sense_key(sk)? {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
 fn attr_value(attr : &Vec<OwnedAttribute>, name : &'static str) -> Option<String> {
     attr.iter().find(|a| a.name.local_name == name).map(|a| a.value.clone())
 }
 
 
 fn read_glosstag_corpus<P : AsRef<Path>>(path : P,
-        wordnet : &WordNetBuilder) -> Result<GlossTagCorpus, WordNetLoadError> {
+        wordnet : &WordNetBuilder, ili_map : &HashMap<WNKey, String>,
+        unmapped : &mut Unmapped) -> Result<GlossTagCorpus, WordNetLoadError> {
     let file = BufReader::new(File::open(path)?);
 
     let parse = EventReader::new(file);
 
     let mut current_id : Option<WNKey> = None;
+    let mut resolved = false;
+    let mut current_wn30 : WNKey = String::new();
+    let mut current_keys : Vec<String> = Vec::new();
+    let mut in_sk = false;
     let mut current_sents : Vec<Gloss> = Vec::new();
     let mut current_sent = Vec::new();
     let mut current_word = GlossTagWord { 
@@ -62,11 +215,18 @@ fn read_glosstag_corpus<P : AsRef<Path>>(path : P,
                             "bad wn30 id"))?;
                     let num : String = wn30id.chars().skip(1).collect();
                     //let id = WNKey::from_str(&format!("{}-{}", num ,pos))?;
-                    let id = format!("{}-{}", num, pos);
-                    current_id = wordnet.get_id_by_pwn30(&id)
-                        .expect("Loading gloss tags without WN 3.0 index")
-                        .map(|x| x.clone());
+                    current_wn30 = format!("{}-{}", num, pos);
+                    current_id = None;
+                    resolved = false;
+                    current_keys = Vec::new();
+                } else if name.local_name == "sk" {
+                    in_sk = true;
                 } else if name.local_name == "gloss" {
+                    // The sense keys precede the glosses of the synset
+                    if !resolved {
+                        current_id = resolve_synset(wordnet, &current_wn30, &current_keys, ili_map)?;
+                        resolved = true;
+                    }
                     current_sents = Vec::new();
                 } else if name.local_name == "aux" {
                     current_sent = Vec::new();
@@ -95,27 +255,31 @@ fn read_glosstag_corpus<P : AsRef<Path>>(path : P,
                     let sk = attr_value(&attributes, "sk") 
                         .ok_or_else(|| WordNetLoadError::Schema(
                             "id does not have sk"))?;
-                    wordnet.get_id_by_sense_key(&sk)?
-                        .map(|ss| {
+                    match wordnet.get_id_by_sense_key(&sk)? {
+                        Some(ss) => {
                             if in_glob {
                                 current_word.glob = Some(ss.to_string())
                             } else {
                                 current_word.synset = Some(ss.to_string()) 
                             }
-                        });
+                        },
+                        None => unmapped.senses += 1
+                    }
                 }
             },
             Ok(XmlEvent::EndElement { name, .. }) => {
                 if name.local_name == "synset" {
+                    if resolved && current_id.is_none() {
+                        unmapped.glosses.push(current_wn30.clone());
+                    }
                     current_id = None;
                 } else if name.local_name == "gloss" {
-                    match current_id.clone() {
-                        Some(ssid) => {
-                            all_sents.insert(ssid.clone(),
-                                current_sents.clone());
-                        },
-                        None => {}
+                    if let Some(ssid) = current_id.clone() {
+                        all_sents.insert(ssid.clone(),
+                            current_sents.clone());
                     }
+                } else if name.local_name == "sk" {
+                    in_sk = false;
                 } else if name.local_name == "aux" {
                     current_sents.push(Gloss {
                         words: current_sent.clone(),
@@ -139,7 +303,11 @@ fn read_glosstag_corpus<P : AsRef<Path>>(path : P,
                 
             },
             Ok(XmlEvent::Characters(s)) => {
-                current_word.text = s;
+                if in_sk {
+                    current_keys.push(s);
+                } else {
+                    current_word.text = s;
+                }
             },
             Ok(_) => {},
             Err(e) => { return Err(WordNetLoadError::Xml(e)); }
@@ -148,16 +316,32 @@ fn read_glosstag_corpus<P : AsRef<Path>>(path : P,
     Ok(all_sents)
 }
 
-pub fn build_glosstags(wordnet : &mut WordNetBuilder)
+/// Load the gloss tag corpus from a directory containing `adj.xml`,
+/// `adv.xml`, `noun.xml` and `verb.xml`. Glosses that cannot be mapped to a
+/// synset are reported and skipped
+pub fn build_glosstags<P : AsRef<Path>>(wordnet : &mut WordNetBuilder, dir : P)
          -> Result<(), WordNetLoadError> {
-    eprintln!("Loading gloss tags (adj)");
-    let mut result = read_glosstag_corpus("data/merged/adj.xml", &wordnet)?;
-    eprintln!("Loading gloss tags (adv)");
-    result.extend(read_glosstag_corpus("data/merged/adv.xml", &wordnet)?);
-    eprintln!("Loading gloss tags (noun)");
-    result.extend(read_glosstag_corpus("data/merged/noun.xml", &wordnet)?);
-    eprintln!("Loading gloss tags (verb)");
-    result.extend(read_glosstag_corpus("data/merged/verb.xml", &wordnet)?);
+    // The ILI mapping is used to find synsets if the WordNet 3.0 index is
+    // not loaded
+    let ili_map = match scheme_path("pwn30")? {
+        Some(ref path) if Path::new(path).exists() => read_ili_map(path)?,
+        _ => HashMap::new()
+    };
+    let mut unmapped = Unmapped::default();
+    let mut result = HashMap::new();
+    for (name, file) in [("adj", "adj.xml"), ("adv", "adv.xml"),
+                         ("noun", "noun.xml"), ("verb", "verb.xml")].iter() {
+        eprintln!("Loading gloss tags ({})", name);
+        result.extend(read_glosstag_corpus(dir.as_ref().join(file), wordnet,
+                                           &ili_map, &mut unmapped)?);
+    }
+    if !unmapped.glosses.is_empty() || unmapped.senses > 0 {
+        eprintln!("Could not map {} glosses and {} sense references in the gloss tags",
+                  unmapped.glosses.len(), unmapped.senses);
+        for id in unmapped.glosses.iter().take(10) {
+            eprintln!("  Unmapped gloss: {}", id);
+        }
+    }
     for (k,v) in result.iter() {
         if let Some(mut s) = wordnet.get_synset(k)? {
             s.gloss = Some(v.clone());
@@ -166,5 +350,3 @@ pub fn build_glosstags(wordnet : &mut WordNetBuilder)
     }
     Ok(())
 }
-
-
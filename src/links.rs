@@ -2,39 +2,481 @@ use std::path::Path;
 use crate::wordnet::{WNKey,WordNetLoadError,WordNetBuilder};
 use std::collections::{HashMap,HashSet};
 use std::io::{BufRead,BufReader};
-use std::fs::{read_dir,File};
-use xml::reader::{EventReader, XmlEvent};
-use std::ffi::OsStr;
+use std::fs::File;
+use std::fmt::{self, Formatter, Result as FormatResult};
+use std::str::{Chars, FromStr};
+use std::iter::Peekable;
+use serde::de::{Visitor, Deserializer, Error as DeError};
+use serde::{Serialize, Serializer, Deserialize};
 use std::iter::FromIterator;
+use crate::verbnet::{load_verbnet,member_synsets};
 
-#[derive(Clone,Debug,Serialize,Deserialize)]
-pub enum LinkType { VerbNet, W3C, Wikipedia }
+/// The file that declares further link sources
+pub const LINK_SOURCES_FILE : &str = "data/link-sources.tab";
+
+/// The type of a link, being one of the built-in sources or the name of a
+/// source declared in the link sources file
+#[derive(Clone,Debug,PartialEq)]
+pub enum LinkType { VerbNet, W3C, Wikipedia, ImageNet, Other(String) }
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f : &mut Formatter) -> FormatResult {
+        f.write_str(match *self {
+            LinkType::VerbNet => "VerbNet",
+            LinkType::W3C => "W3C",
+            LinkType::Wikipedia => "Wikipedia",
+            LinkType::ImageNet => "ImageNet",
+            LinkType::Other(ref name) => name
+        })
+    }
+}
+
+impl FromStr for LinkType {
+    type Err = WordNetLoadError;
+    fn from_str(s : &str) -> Result<LinkType, WordNetLoadError> {
+        match s {
+            "VerbNet" => Ok(LinkType::VerbNet),
+            "W3C" => Ok(LinkType::W3C),
+            "Wikipedia" => Ok(LinkType::Wikipedia),
+            "ImageNet" => Ok(LinkType::ImageNet),
+            _ => Ok(LinkType::Other(s.to_string()))
+        }
+    }
+}
+
+impl Serialize for LinkType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for LinkType {
+    fn deserialize<D>(deserializer: D) -> Result<LinkType, D::Error>
+        where D: Deserializer<'de> {
+        deserializer.deserialize_str(LinkTypeVisitor)
+    }
+}
+
+struct LinkTypeVisitor;
+
+impl<'de> Visitor<'de> for LinkTypeVisitor {
+    type Value = LinkType;
+
+    fn expecting(&self, formatter : &mut Formatter) -> FormatResult {
+        formatter.write_str("The name of a link source")
+    }
+
+    fn visit_str<E>(self, value : &str) -> Result<LinkType, E>  where E : DeError {
+        LinkType::from_str(value)
+            .map_err(|e| E::custom(e))
+    }
+}
+
+impl LinkType {
+    /// The URL of a target of one of the built-in sources
+    fn url(&self, target : &str) -> Option<String> {
+        match *self {
+            LinkType::VerbNet => Some(format!("https://verbs.colorado.edu/verb-index/vn/{}.php", target)),
+            LinkType::W3C => Some(target.to_string()),
+            LinkType::Wikipedia => Some(format!("https://en.wikipedia.org/wiki/{}", target)),
+            LinkType::ImageNet => Some(format!("http://www.image-net.org/synset?wnid={}", target)),
+            LinkType::Other(_) => None
+        }
+    }
+}
+
+fn see_also() -> String { "rdfs:seeAlso".to_string() }
 
 #[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct Link {
     pub link_type : LinkType,
-    pub target : String
+    pub target : String,
+    #[serde(default)]
+    pub url : Option<String>,
+    #[serde(default)]
+    pub label : Option<String>,
+    #[serde(default="see_also")]
+    pub property : String,
+    /// The language of the target, for Wikipedia articles
+    #[serde(default)]
+    pub language : Option<String>,
+    /// The title of the target, for Wikipedia articles
+    #[serde(default)]
+    pub title : Option<String>
+}
+
+impl Link {
+    /// A link to one of the built-in sources
+    pub fn new(link_type : LinkType, target : String) -> Link {
+        Link {
+            url: link_type.url(&target),
+            label: Some(link_type.to_string()),
+            property: see_also(),
+            language: None,
+            title: None,
+            link_type,
+            target
+        }
+    }
+
+    /// A link to an article in a language edition of Wikipedia
+    pub fn wikipedia(language : String, article : String) -> Link {
+        Link {
+            link_type: LinkType::Wikipedia,
+            url: Some(format!("https://{}.wikipedia.org/wiki/{}", language, article)),
+            label: Some(LinkType::Wikipedia.to_string()),
+            property: see_also(),
+            language: Some(language),
+            title: Some(decode_title(&article)),
+            target: article
+        }
+    }
+}
+
+/// The format of the file of a link source
+#[derive(Clone,Debug,PartialEq)]
+pub enum LinkFormat { Tsv, Csv, Turtle }
+
+/// The identifiers used for synsets in a link source
+#[derive(Clone,Debug,PartialEq)]
+pub enum LinkKey { Id, Ili, SenseKey, Pwn30 }
+
+/// The RDF properties that may link a synset to the target of a link source
+const LINK_PROPERTIES : [&str; 3] = ["rdfs:seeAlso", "skos:closeMatch", "owl:sameAs"];
+
+/// A source of links declared in the link sources file. Each line of the
+/// file gives the name, the path, the format (`tsv`, `csv` or `ttl`), the
+/// key type (`id`, `ili`, `sense_key` or `pwn30`), the URL template, where
+/// `{}` is replaced by the target, the label and optionally the RDF property
+/// of the links (one of `LINK_PROPERTIES`, by default `rdfs:seeAlso`)
+#[derive(Clone,Debug)]
+pub struct LinkSource {
+    pub name : String,
+    pub path : String,
+    pub format : LinkFormat,
+    pub key : LinkKey,
+    pub url_template : String,
+    pub label : String,
+    pub property : String
+}
+
+impl LinkSource {
+    fn link(&self, target : String) -> Link {
+        Link {
+            link_type: LinkType::Other(self.name.clone()),
+            url: Some(self.url_template.replace("{}", &target)),
+            label: Some(self.label.clone()),
+            property: self.property.clone(),
+            language: None,
+            title: None,
+            target
+        }
+    }
+
+    fn resolve(&self, key : &str, wordnet : &WordNetBuilder) -> Result<Option<WNKey>, WordNetLoadError> {
+        match self.key {
+            LinkKey::Id => Ok(wordnet.get_synset(&key.to_string())?.map(|s| s.id)),
+            LinkKey::Ili => wordnet.get_id_by_ili(key),
+            LinkKey::SenseKey => wordnet.get_id_by_sense_key(key),
+            LinkKey::Pwn30 => wordnet.get_id_by_pwn30(&key.to_string())
+        }
+    }
+}
+
+/// The link sources loaded by default, which link to the Wikidata items
+/// mapped to ILIs
+pub fn default_link_sources() -> Vec<LinkSource> {
+    vec![LinkSource {
+        name: "wikidata".to_string(),
+        path: "data/ili-map-wikidata.ttl".to_string(),
+        format: LinkFormat::Turtle,
+        key: LinkKey::Ili,
+        url_template: "http://www.wikidata.org/entity/{}".to_string(),
+        label: "Wikidata".to_string(),
+        property: "skos:closeMatch".to_string()
+    }]
+}
+
+/// The default link sources and those declared in the link sources file,
+/// which replace any default source of the same name
+pub fn configured_link_sources() -> Result<Vec<LinkSource>, WordNetLoadError> {
+    let mut sources = default_link_sources();
+    if Path::new(LINK_SOURCES_FILE).exists() {
+        for source in read_link_sources(LINK_SOURCES_FILE)? {
+            sources.retain(|s| s.name != source.name);
+            sources.push(source);
+        }
+    }
+    Ok(sources)
+}
+
+/// Read the declared link sources
+pub fn read_link_sources<P : AsRef<Path>>(path : P) -> Result<Vec<LinkSource>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if !line.starts_with("#") && !line.is_empty() {
+            let elems : Vec<&str> = line.split("\t").collect();
+            if elems.len() != 6 && elems.len() != 7 {
+                return Err(WordNetLoadError::Schema("Bad line in link sources file"));
+            }
+            let format = match elems[2] {
+                "tsv" => LinkFormat::Tsv,
+                "csv" => LinkFormat::Csv,
+                "ttl" => LinkFormat::Turtle,
+                _ => return Err(WordNetLoadError::Schema("Bad format in link sources file"))
+            };
+            let key = match elems[3] {
+                "id" => LinkKey::Id,
+                "ili" => LinkKey::Ili,
+                "sense_key" => LinkKey::SenseKey,
+                "pwn30" => LinkKey::Pwn30,
+                _ => return Err(WordNetLoadError::Schema("Bad key type in link sources file"))
+            };
+            let property = elems.get(6).map(|p| p.to_string()).unwrap_or_else(see_also);
+            if !LINK_PROPERTIES.contains(&property.as_str()) {
+                return Err(WordNetLoadError::Schema("Bad property in link sources file"));
+            }
+            result.push(LinkSource {
+                name: elems[0].to_string(),
+                path: elems[1].to_string(),
+                format,
+                key,
+                url_template: elems[4].to_string(),
+                label: elems[5].to_string(),
+                property
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// A term of an N-Triples statement
+#[derive(Clone,Debug,PartialEq)]
+enum Term { Iri(String), Literal(String), Blank(String) }
+
+impl Term {
+    /// The IRI, the lexical form of a literal or the blank node label
+    fn value(&self) -> &str {
+        match *self {
+            Term::Iri(ref s) | Term::Literal(ref s) | Term::Blank(ref s) => s
+        }
+    }
+
+    /// The local name of an IRI (the part after the last `/` or `#`) or the
+    /// value of a literal
+    fn local_name(&self) -> &str {
+        match *self {
+            Term::Iri(ref iri) => match iri.rfind(['/', '#']) {
+                Some(i) => &iri[i+1..],
+                None => iri
+            },
+            _ => self.value()
+        }
+    }
+}
+
+/// Read the character after a `\` in an IRI or a literal
+fn unescape(chars : &mut Peekable<Chars>) -> Option<char> {
+    match chars.next()? {
+        't' => Some('\t'),
+        'b' => Some('\u{8}'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        'f' => Some('\u{c}'),
+        'u' => char::from_u32(u32::from_str_radix(&chars.take(4).collect::<String>(), 16).ok()?),
+        'U' => char::from_u32(u32::from_str_radix(&chars.take(8).collect::<String>(), 16).ok()?),
+        c => Some(c)
+    }
+}
+
+fn parse_term(chars : &mut Peekable<Chars>) -> Option<Term> {
+    while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+        chars.next();
+    }
+    match chars.next()? {
+        '<' => {
+            let mut iri = String::new();
+            loop {
+                match chars.next()? {
+                    '>' => return Some(Term::Iri(iri)),
+                    '\\' => iri.push(unescape(chars)?),
+                    c => iri.push(c)
+                }
+            }
+        },
+        '"' => {
+            let mut lit = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => lit.push(unescape(chars)?),
+                    c => lit.push(c)
+                }
+            }
+            // Skip the language tag or datatype
+            match chars.peek() {
+                Some(&'@') => {
+                    while chars.peek().map(|c| !c.is_whitespace()).unwrap_or(false) {
+                        chars.next();
+                    }
+                },
+                Some(&'^') => {
+                    chars.next();
+                    chars.next();
+                    parse_term(chars)?;
+                },
+                _ => {}
+            }
+            Some(Term::Literal(lit))
+        },
+        '_' => {
+            if chars.next()? != ':' {
+                return None;
+            }
+            let mut label = String::new();
+            while chars.peek().map(|c| !c.is_whitespace() && *c != '.').unwrap_or(false) {
+                label.push(chars.next()?);
+            }
+            Some(Term::Blank(label))
+        },
+        _ => None
+    }
+}
+
+/// Parse a line of N-Triples into its subject, predicate and object. Blank
+/// lines, comments and malformed lines give `None`
+fn parse_ntriple(line : &str) -> Option<(Term, Term, Term)> {
+    let mut chars = line.chars().peekable();
+    let subject = parse_term(&mut chars)?;
+    let predicate = parse_term(&mut chars)?;
+    let object = parse_term(&mut chars)?;
+    if chars.find(|c| !c.is_whitespace()) == Some('.') {
+        Some((subject, predicate, object))
+    } else {
+        None
+    }
+}
+
+/// The language edition and the article name of a DBpedia resource, e.g.,
+/// `http://fr.dbpedia.org/resource/Chat` gives `fr` and `Chat`
+fn dbpedia_article(iri : &str) -> Option<(String, String)> {
+    let rest = iri.strip_prefix("http://").or_else(|| iri.strip_prefix("https://"))?;
+    let i = rest.find('/')?;
+    let (host, path) = rest.split_at(i);
+    let article = path.strip_prefix("/resource/")?;
+    let language = if host == "dbpedia.org" {
+        "en"
+    } else {
+        host.strip_suffix(".dbpedia.org")?
+    };
+    if article.is_empty() {
+        None
+    } else {
+        Some((language.to_string(), article.to_string()))
+    }
+}
+
+/// The title of an article from its name in a URL, by decoding the percent
+/// escapes and replacing underscores with spaces
+fn decode_title(article : &str) -> String {
+    let bytes = article.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = ::std::str::from_utf8(&bytes[i+1..i+3]).ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            if let Some(b) = hex {
+                decoded.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(if bytes[i] == b'_' { b' ' } else { bytes[i] });
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Read the key and target of each line of a link source. For Turtle, the
+/// key is the local name of the subject and the target is the local name of
+/// the object (or its value, if it is a literal)
+fn read_link_source(source : &LinkSource) -> Result<Vec<(String, String)>, WordNetLoadError> {
+    let file = BufReader::new(File::open(&source.path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if line.starts_with("#") || line.trim().is_empty() {
+            continue;
+        }
+        let pair = match source.format {
+            LinkFormat::Tsv => {
+                let mut elems = line.split("\t");
+                (elems.next(), elems.next())
+            },
+            LinkFormat::Csv => {
+                let mut elems = line.split(",").map(|e| e.trim_matches('"'));
+                (elems.next(), elems.next())
+            },
+            LinkFormat::Turtle => {
+                if let Some((subject, _, object)) = parse_ntriple(&line) {
+                    result.push((subject.local_name().to_string(), object.local_name().to_string()));
+                }
+                continue;
+            }
+        };
+        if let (Some(key), Some(target)) = pair {
+            result.push((key.to_string(), target.to_string()));
+        }
+    }
+    Ok(result)
 }
 
-/// Load all links to VerbNet, W3C and Wikipedia
+/// Load the links of the sources declared in `data/link-sources.tab`. These
+/// are loaded whether or not the built-in sources are
+pub fn load_link_sources(wordnet : &mut WordNetBuilder) -> Result<(), WordNetLoadError> {
+    for source in configured_link_sources()? {
+        eprintln!("Loading {} Links", source.name);
+        let pairs = read_link_source(&source).unwrap_or_else(|e| {
+            eprintln!("Failed to load {}: {}", source.name, e);
+            Vec::new()
+        });
+        let mut links = Vec::new();
+        for (key, target) in pairs {
+            if let Some(id) = source.resolve(&key, wordnet)? {
+                links.push((id, source.link(target)));
+            }
+        }
+        wordnet.insert_links(links)?;
+    }
+    Ok(())
+}
+
+/// Load all links to VerbNet, W3C, Wikipedia and ImageNet, along with the
+/// VerbNet classes
 pub fn load_links(wordnet : &mut WordNetBuilder) -> Result<(), WordNetLoadError> {
     {
         eprintln!("Loading VerbNet");
-        let verbs = load_all_verbs().unwrap_or_else(|e| {
+        let classes = load_verbnet().unwrap_or_else(|e| {
             eprintln!("Failed to load VerbNet: {}", e);
-            HashMap::new()
+            Vec::new()
         });
         let mut links = HashSet::new();
-        for (sense_key, vs) in verbs {
-            if let Some(key) = wordnet.get_id_by_sense_key(&sense_key)? {
-                for v in vs {
-                    links.insert((key.clone(), v));
-                    //wordnet.insert_link(&key, LinkType::VerbNet, v)?;
-                }
+        let mut members = Vec::new();
+        for class in classes.iter() {
+            let synsets = member_synsets(class, |sk| wordnet.get_id_by_sense_key(sk))?;
+            for key in synsets.iter() {
+                links.insert((key.clone(), class.root.clone()));
             }
+            members.push((class, synsets));
         }
-        
-        wordnet.insert_links(LinkType::VerbNet, Vec::from_iter(links))?;
+        wordnet.insert_verbnet(members)?;
+        wordnet.insert_links(Vec::from_iter(links).into_iter()
+            .map(|(key, v)| (key, Link::new(LinkType::VerbNet, v)))
+            .collect())?;
     }
     {
         eprintln!("Loading W3C Links");
@@ -44,10 +486,9 @@ pub fn load_links(wordnet : &mut WordNetBuilder) -> Result<(), WordNetLoadError>
         });
         let mut links = Vec::new();
         for (key, target) in w3c {
-            links.push((key, target));
-            //wordnet.insert_link(&key, LinkType::W3C, target)?;
+            links.push((key, Link::new(LinkType::W3C, target)));
         }
-        wordnet.insert_links(LinkType::W3C, links)?;
+        wordnet.insert_links(links)?;
     }
     {
         eprintln!("Loading Wikipedia Links");
@@ -56,72 +497,25 @@ pub fn load_links(wordnet : &mut WordNetBuilder) -> Result<(), WordNetLoadError>
             HashMap::new()
         });
         let mut links = Vec::new();
-        for (key, targets) in wwim {
-            for target in targets {
-                links.push((key.clone(), target));
-                //wordnet.insert_link(&key, LinkType::Wikipedia, target)?;
+        for (key, articles) in wwim {
+            for (language, article) in articles {
+                links.push((key.clone(), Link::wikipedia(language, article)));
             }
         }
-        wordnet.insert_links(LinkType::Wikipedia, links)?;
+        wordnet.insert_links(links)?;
     }
-    Ok(())
-}
-
-fn load_verbs<P : AsRef<Path>>(path : P) -> Result<HashMap<String,Vec<String>>, WordNetLoadError> {
-    let file = BufReader::new(File::open(path)?);
-    let parse = EventReader::new(file);
-
-    let mut vnid : Option<String> = None;
-    let mut wn2vn : HashMap<String, Vec<String>> = HashMap::new();
-
-    for e in parse {
-        match e {
-            Ok(XmlEvent::StartElement{ name, attributes, .. }) => {
-                if name.local_name == "VNCLASS" {
-                    vnid = attributes.iter().find(|a| a.name.local_name == "ID")
-                        .map(|a| a.value.clone());
-                } else if name.local_name == "MEMBER" {
-                    let wn_str = attributes.iter()
-                        .find(|a| a.name.local_name == "wn")
-                        .map(|a| a.value.clone())
-                        .unwrap_or_else(|| "".to_string());
-                    let elems = wn_str.split(" ");
-                    match vnid {
-                        Some(ref vn) => {
-                            for wn in elems {
-                                wn2vn.entry(format!("{}::", wn))
-                                    .or_insert_with(|| Vec::new())
-                                    .push(vn.clone());
-                            }
-                        },
-                        None => {
-                            eprintln!("Members without id");
-                        }
-                    }
-                }
-            },
-            Ok(_) => {},
-            Err(e) => return Err(WordNetLoadError::Xml(e))
-        }
-    }
-
-    Ok(wn2vn)
-}
-
-fn load_all_verbs() -> Result<HashMap<String, Vec<String>>, WordNetLoadError> {
-    let paths = read_dir("data/verbnet")?;
-    let mut verbnet_links = HashMap::new();
-
-    for path in paths {
-        let path = path?.path();
-        if path.extension() == Some(OsStr::new("xml")) {
-            verbnet_links.extend(load_verbs(path)?);
-        }
+    {
+        eprintln!("Loading ImageNet Links");
+        let links = wordnet.get_pwn30_ids().into_iter()
+            .filter(|(pwn30, _)| pwn30.ends_with("-n"))
+            .map(|(pwn30, id)| (id, Link::new(LinkType::ImageNet,
+                format!("n{}", &pwn30[..pwn30.len()-2]))))
+            .collect();
+        wordnet.insert_links(links)?;
     }
-    Ok(verbnet_links)
+    Ok(())
 }
 
-
 fn load_w3c(wordnet : &WordNetBuilder) -> Result<HashMap<WNKey, String>,WordNetLoadError> {
     let file = BufReader::new(File::open("data/w3c-wn20.csv")?);
 
@@ -145,25 +539,20 @@ fn load_w3c(wordnet : &WordNetBuilder) -> Result<HashMap<WNKey, String>,WordNetL
     Ok(map)
 }
 
-fn load_wwim(wordnet : &WordNetBuilder) -> Result<HashMap<WNKey, Vec<String>>, WordNetLoadError> {
+/// Read the Wikipedia articles of each ILI from the DBpedia resources it is
+/// linked to, giving the language edition and the name of each article
+fn load_wwim(wordnet : &WordNetBuilder) -> Result<HashMap<WNKey, Vec<(String, String)>>, WordNetLoadError> {
     let file = BufReader::new(File::open("data/ili-map-dbpedia.ttl")?);
 
     let mut map = HashMap::new();
 
     for line in file.lines() {
-        let line = line?;
-        if !line.starts_with("#") {
-            let mut elems = line.split(" ");
-            if let Some(ili_url) = elems.next() {
-                elems.next();
-                if let Some(dbpedia_url) = elems.next() {
-                    let ili = ili_url[30..(ili_url.len()-1)].to_string();
-                    let wiki_id = dbpedia_url[29..(dbpedia_url.len()-1)].to_string();
-                    if let Some(id) = wordnet.get_id_by_ili(&ili)? {
-                        map.entry(id.clone())
-                            .or_insert_with(|| Vec::new())
-                            .push(wiki_id);
-                    }
+        if let Some((ili, _, Term::Iri(resource))) = parse_ntriple(&line?) {
+            if let Some(article) = dbpedia_article(&resource) {
+                if let Some(id) = wordnet.get_id_by_ili(ili.local_name())? {
+                    map.entry(id.clone())
+                        .or_insert_with(Vec::new)
+                        .push(article);
                 }
             }
         }
@@ -172,6 +561,52 @@ fn load_wwim(wordnet : &WordNetBuilder) -> Result<HashMap<WNKey, Vec<String>>, W
     Ok(map)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ntriple() {
+        assert_eq!(parse_ntriple("<http://ili.globalwordnet.org/ili/i46593> <http://www.w3.org/2004/02/skos/core#closeMatch> <http://www.wikidata.org/entity/Q146> ."),
+            Some((Term::Iri("http://ili.globalwordnet.org/ili/i46593".to_string()),
+                  Term::Iri("http://www.w3.org/2004/02/skos/core#closeMatch".to_string()),
+                  Term::Iri("http://www.wikidata.org/entity/Q146".to_string()))));
+        assert_eq!(parse_ntriple("_:b0 <http://www.w3.org/2000/01/rdf-schema#label> \"Caf\\u00E9 \\\"noir\\\"\"@fr ."),
+            Some((Term::Blank("b0".to_string()),
+                  Term::Iri("http://www.w3.org/2000/01/rdf-schema#label".to_string()),
+                  Term::Literal("Café \"noir\"".to_string()))));
+        assert_eq!(parse_ntriple("<a> <b> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer>."),
+            Some((Term::Iri("a".to_string()), Term::Iri("b".to_string()),
+                  Term::Literal("1".to_string()))));
+        assert_eq!(parse_ntriple("<a> <b> <c>"), None);
+        assert_eq!(parse_ntriple("<a> <b> \"unterminated ."), None);
+        assert_eq!(parse_ntriple("<a> <b>"), None);
+    }
 
+    #[test]
+    fn test_local_name() {
+        assert_eq!(Term::Iri("http://www.wikidata.org/entity/Q146".to_string()).local_name(), "Q146");
+        assert_eq!(Term::Iri("http://www.w3.org/2004/02/skos/core#closeMatch".to_string()).local_name(), "closeMatch");
+        assert_eq!(Term::Literal("Q146".to_string()).local_name(), "Q146");
+    }
 
+    #[test]
+    fn test_dbpedia_article() {
+        assert_eq!(dbpedia_article("http://dbpedia.org/resource/Cat"),
+                   Some(("en".to_string(), "Cat".to_string())));
+        assert_eq!(dbpedia_article("https://fr.dbpedia.org/resource/Chat"),
+                   Some(("fr".to_string(), "Chat".to_string())));
+        assert_eq!(dbpedia_article("http://dbpedia.org/ontology/Animal"), None);
+        assert_eq!(dbpedia_article("http://dbpedia.org/resource/"), None);
+        assert_eq!(dbpedia_article("http://example.org/resource/Cat"), None);
+    }
 
+    #[test]
+    fn test_decode_title() {
+        assert_eq!(decode_title("Domestic_cat"), "Domestic cat");
+        assert_eq!(decode_title("Caf%C3%A9"), "Café");
+        assert_eq!(decode_title("100%25"), "100%");
+        assert_eq!(decode_title("50%"), "50%");
+        assert_eq!(decode_title("%zz"), "%zz");
+    }
+}
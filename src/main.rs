@@ -16,11 +16,24 @@ mod wordnet;
 mod glosstag;
 mod omwn;
 mod links;
+mod fuzzy;
+mod morphy;
+mod normalize;
+mod pronunciation;
+mod taxonomy;
+mod similarity;
+mod relations;
+mod schemes;
+mod translate;
+mod coverage;
+mod verbnet;
 mod wordnet_read;
 
 use std::str::FromStr;
-use wordnet::{WNKey, WordNet};
+use wordnet::{WNKey, WordNet, PatternQuery};
 use wordnet_model::Synset;
+use glosstag::{annotated_glosses, GlossText};
+use similarity::Measure;
 use clap::{App, Arg, ArgMatches};
 use std::process::exit;
 use rocket::Request;
@@ -28,20 +41,30 @@ use rocket::request::{FromRequest,Outcome};
 use rocket::http::ContentType;
 use rocket::response::content::{RawHtml, RawJson};
 use rocket::response::Redirect;
+use rocket::data::{Data, ToByteUnit};
 use std::env;
 use std::fs::File;
 use std::fs;
 use std::path::Path;
 use std::ops::Deref;
 use handlebars::Handlebars;
-use std::collections::HashMap;
+use std::collections::{BTreeMap,HashMap};
+use std::collections::hash_map::Entry;
 use rocket::config::Config as RocketConfig;
 use once_cell::sync::Lazy;
 use std::sync::{Mutex, MutexGuard};
 
+/// A synset with its tagged glosses, for the RDF templates
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct SynsetHB {
+    #[serde(flatten)]
+    synset : Synset,
+    glosses : Vec<GlossText>
+}
+
 #[derive(Clone,Debug,Serialize,Deserialize)]
 struct SynsetsHB {
-    synsets : Vec<Synset>,
+    synsets : Vec<SynsetHB>,
     entries : HashMap<String,HashMap<String, Vec<Synset>>>,
     index : String,
     name : String,
@@ -80,7 +103,10 @@ fn make_synsets_hb(synsets : Vec<Synset>, index : String,
         WordNetSite::Polylingual => "http://polylingwn.linguistic-lod.org"
     };
     SynsetsHB {
-        synsets,
+        synsets: synsets.into_iter().map(|synset| SynsetHB {
+            glosses: annotated_glosses(&synset.gloss),
+            synset
+        }).collect(),
         entries,
         index,
         name,
@@ -259,6 +285,48 @@ fn get_static(name : &str) -> Option<StaticResponse> {
     }
 }
 
+/// The result of a lemma lookup. If the lemma was not found, the synsets
+/// may have been found through the base forms of the query instead, and
+/// if nothing was found the closest lemmas are suggested. Only the base
+/// forms and suggestions are served, by `/json/suggest`
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct LemmaLookup {
+    #[serde(skip)]
+    synsets : Vec<Synset>,
+    base_forms : Vec<String>,
+    suggestions : Vec<String>
+}
+
+fn lookup_lemma(wordnet : &WordNet, lemma : &str, lang : &str)
+        -> Result<LemmaLookup, String> {
+    let synsets = wordnet.get_by_lemma(lemma, lang)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if !synsets.is_empty() {
+        return Ok(LemmaLookup { synsets, base_forms: Vec::new(), suggestions: Vec::new() });
+    }
+    let (base_forms, synsets) = if lang == "en" {
+        wordnet.get_by_inflected_form(lemma, lang)
+            .map_err(|e| format!("Database error: {}", e))?
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let suggestions = if synsets.is_empty() {
+        wordnet.suggest_lemmas(lemma, lang, 10)
+            .map_err(|e| format!("Database error: {}", e))?
+    } else {
+        Vec::new()
+    };
+    Ok(LemmaLookup { synsets, base_forms, suggestions })
+}
+
+/// The name of the scheme served at a prefix, or the index itself if it is
+/// not a prefix
+fn scheme_name(wordnet : &WordNet, index : &str) -> Result<String, String> {
+    Ok(wordnet.get_scheme_by_prefix(index)
+        .map_err(|e| format!("Database error: {}", e))?
+        .unwrap_or_else(|| index.to_string()))
+}
+
 fn get_synsets(wordnet : &WordNet, index : &str, id : &str) 
         -> Result<Vec<Synset>, String> {
     let wn = if index == "id" {
@@ -266,14 +334,8 @@ fn get_synsets(wordnet : &WordNet, index : &str, id : &str)
                 .map_err(|_| format!("Not a WordNet ID"))?)
             .map_err(|e| format!("Database error: {}", e))?
             .ok_or(format!("Synset Not Found"))?.clone()]
-    } else if index == "lemma" {
-        wordnet.get_by_lemma(id, "en")
-            .map_err(|e| format!("Database error: {}", e))?
-            .iter().map(|x| (*x).clone()).collect()
-    } else if index.starts_with("lemma") {
-        wordnet.get_by_lemma(id, &index[6..])
-            .map_err(|e| format!("Database error: {}", e))?
-            .iter().map(|x| (*x).clone()).collect()
+    } else if let Some(lang) = omwn::lemma_language(index) {
+        lookup_lemma(wordnet, id, lang)?.synsets
     } else if index == "ili" {
         vec![wordnet.get_by_ili(id)
                 .map_err(|e| format!("Database Error: {}", e))?
@@ -283,7 +345,7 @@ fn get_synsets(wordnet : &WordNet, index : &str, id : &str)
                 .map_err(|e| format!("Database Error: {}", e))?
                 .ok_or(format!("Synset Not Found"))?.clone()]
      } else {
-        vec![wordnet.get_by_old_id(index, &WNKey::from_str(id)
+        vec![wordnet.get_by_old_id(&scheme_name(wordnet, index)?, &WNKey::from_str(id)
                 .map_err(|_| format!("Not a WordNet Key"))?)
                 .map_err(|e| format!("Database Error: {}", e))?
                 .ok_or(format!("Synset Not Found"))?.clone()]
@@ -296,12 +358,332 @@ fn get_synsets(wordnet : &WordNet, index : &str, id : &str)
 fn synset(index : &str, id : &str)
         -> Result<RawJson<String>,String> {
     let status = WordNetState::get();
-    let synsets = get_synsets(&status.wordnet, &index, &id)?;
+    if let Some(lang) = omwn::lemma_language(index) {
+        if !status.wordnet.has_language(lang)
+                .map_err(|e| format!("Database error: {}", e))? {
+            return Err("Unknown language".to_string());
+        }
+    }
+    let synsets = get_synsets(&status.wordnet, index, id)?;
     let json = serde_json::to_string(&synsets)
         .map_err(|e| format!("Failed to serialize synset: {}", e))?;
     Ok(RawJson(json))
 }
 
+/// The base forms a lemma lookup fell back to and the closest lemmas if
+/// nothing was found
+#[get("/json/suggest/<lang>/<lemma>")]
+fn suggest(lang : &str, lemma : &str) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let lang = omwn::language_code(lang);
+    if !status.wordnet.has_language(lang)
+            .map_err(|e| format!("Database error: {}", e))? {
+        return Err("Unknown language".to_string());
+    }
+    let result = lookup_lemma(&status.wordnet, lemma, lang)?;
+    let json = serde_json::to_string(&result)
+        .map_err(|e| format!("Failed to serialize suggestions: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// Search for lemmas by a pattern such as `c?t` or `*ology` (`?` must be
+/// escaped in the URL as `%3F` or can be written as `_`)
+#[get("/json/pattern/<lang>/<pattern>?<pos>&<min_length>&<max_length>&<definition>&<limit>")]
+fn pattern_search(lang : &str, pattern : &str, pos : Option<&str>,
+                  min_length : Option<u32>, max_length : Option<u32>,
+                  definition : Option<&str>, limit : Option<u32>)
+        -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let lemmas = status.wordnet.list_by_pattern(&PatternQuery {
+        pattern,
+        language: lang,
+        pos,
+        min_length,
+        max_length,
+        definition,
+        limit: limit.unwrap_or(100).min(1000)
+    }).map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&lemmas)
+        .map_err(|e| format!("Failed to serialize lemmas: {}", e))?;
+    Ok(RawJson(json))
+}
+
+#[get("/json/rhymes/<lang>/<lemma>?<variety>&<limit>")]
+fn rhymes(lang : &str, lemma : &str, variety : Option<&str>, limit : Option<u32>)
+        -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let matches = status.wordnet.get_rhymes(lemma, lang, variety,
+                                            limit.unwrap_or(100).min(1000))
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&matches)
+        .map_err(|e| format!("Failed to serialize rhymes: {}", e))?;
+    Ok(RawJson(json))
+}
+
+#[get("/json/homophones/<lang>/<lemma>?<variety>&<limit>")]
+fn homophones(lang : &str, lemma : &str, variety : Option<&str>, limit : Option<u32>)
+        -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let matches = status.wordnet.get_homophones(lemma, lang, variety,
+                                                limit.unwrap_or(100).min(1000))
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&matches)
+        .map_err(|e| format!("Failed to serialize homophones: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// Search for lemmas by a pattern over their IPA pronunciation, e.g.,
+/// `*æt` (`?` must be escaped in the URL as `%3F`)
+#[get("/json/ipa/<lang>/<pattern>?<variety>&<limit>")]
+fn ipa_search(lang : &str, pattern : &str, variety : Option<&str>, limit : Option<u32>)
+        -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let matches = status.wordnet.list_by_ipa(pattern, lang, variety,
+                                             limit.unwrap_or(100).min(1000))
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&matches)
+        .map_err(|e| format!("Failed to serialize pronunciations: {}", e))?;
+    Ok(RawJson(json))
+}
+
+#[get("/json/hypernym_paths/<id>?<limit>")]
+fn hypernym_paths(id : &str, limit : Option<usize>) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    status.wordnet.get_synset(&id.to_string())
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Synset Not Found".to_string())?;
+    let paths = taxonomy::hypernym_paths(&status.wordnet, &id.to_string(),
+                                         limit.unwrap_or(100).min(1000))
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&paths)
+        .map_err(|e| format!("Failed to serialize paths: {}", e))?;
+    Ok(RawJson(json))
+}
+
+#[get("/json/hyponyms/<id>?<depth>&<limit>")]
+fn hyponym_tree(id : &str, depth : Option<usize>, limit : Option<usize>)
+        -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let tree = taxonomy::hyponym_tree(&status.wordnet, &id.to_string(),
+                                      depth.unwrap_or(3), limit.unwrap_or(1000).min(10000))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Synset Not Found".to_string())?;
+    let json = serde_json::to_string(&tree)
+        .map_err(|e| format!("Failed to serialize tree: {}", e))?;
+    Ok(RawJson(json))
+}
+
+#[get("/json/closure/<rel_type>/<id>?<limit>")]
+fn closure(rel_type : &str, id : &str, limit : Option<usize>)
+        -> Result<RawJson<String>, String> {
+    if relations::registry().get(rel_type).is_none() {
+        return Err(format!("Unknown relation type: {}", rel_type));
+    }
+    let status = WordNetState::get();
+    status.wordnet.get_synset(&id.to_string())
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Synset Not Found".to_string())?;
+    let synsets = taxonomy::closure(&status.wordnet, &id.to_string(), rel_type,
+                                    limit.unwrap_or(1000).min(10000))
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&synsets)
+        .map_err(|e| format!("Failed to serialize synsets: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The largest request accepted by the batch endpoint
+const BATCH_LIMIT_MIB : u32 = 10;
+
+/// A single lookup in a batch request
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct BatchQuery {
+    index : String,
+    key : String
+}
+
+/// The result of a single lookup in a batch request, either the synsets
+/// found or an error
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct BatchResult {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    synsets : Option<Vec<Synset>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    error : Option<String>
+}
+
+/// Look up many keys in one request. The body is a JSON list of objects
+/// with an `index` (any index accepted by `/json/<index>/<id>`) and a
+/// `key`, and the result is an object giving the result for each index and
+/// key
+#[post("/json/batch", data = "<data>")]
+async fn batch(data : Data<'_>) -> Result<RawJson<String>, String> {
+    let body = data.open(BATCH_LIMIT_MIB.mebibytes()).into_string().await
+        .map_err(|e| format!("Could not read request: {}", e))?;
+    if !body.is_complete() {
+        return Err(format!("Request is larger than {} MiB", BATCH_LIMIT_MIB));
+    }
+    let queries : Vec<BatchQuery> = serde_json::from_str(&body)
+        .map_err(|e| format!("Bad request: {}", e))?;
+    let mut results : BTreeMap<String, BTreeMap<String, BatchResult>> = BTreeMap::new();
+    let status = WordNetState::get();
+    let schemes = status.wordnet.get_schemes()
+        .map_err(|e| format!("Database error: {}", e))?;
+    // Schemes are queried by name but may be given by their prefix, as for
+    // `scheme_name`
+    let mut valid = Vec::new();
+    let mut requested = Vec::new();
+    for q in queries {
+        let name = schemes.iter().find(|s| s.prefix == q.index)
+            .map(|s| s.name.clone())
+            .unwrap_or_else(|| q.index.clone());
+        if q.index == "id" || q.index == "ili" || q.index == "sense_key"
+            || omwn::lemma_language(&q.index).is_some() || schemes.iter().any(|s| s.name == name) {
+            valid.push((name.clone(), q.key.clone()));
+            requested.push((q.index, name, q.key));
+        } else {
+            results.entry(q.index).or_default()
+                .insert(q.key, BatchResult { synsets: None, error: Some("Unknown index".to_string()) });
+        }
+    }
+    let found = status.wordnet.get_batch(&valid)
+        .map_err(|e| format!("Database error: {}", e))?;
+    for (index, name, key) in requested {
+        let synsets = found.get(&name).and_then(|f| f.get(&key));
+        let result = match synsets {
+            Some(synsets) => BatchResult { synsets: Some(synsets.clone()), error: None },
+            None => BatchResult { synsets: None, error: Some("Synset Not Found".to_string()) }
+        };
+        results.entry(index).or_default()
+            .entry(key).or_insert(result);
+    }
+    let json = serde_json::to_string(&results)
+        .map_err(|e| format!("Failed to serialize results: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// Map an identifier from one scheme to another, e.g., `pwn20` to `pwn30`,
+/// `pwn30` to `ili` or `sense_key` to `id`
+#[get("/json/map/<from>/<to>/<id>")]
+fn map_id(from : &str, to : &str, id : &str) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let known = status.wordnet.is_id_scheme(from)
+        .and_then(|f| Ok(f && status.wordnet.is_id_scheme(to)?))
+        .map_err(|e| format!("Database error: {}", e))?;
+    if !known {
+        return Err("Unknown identifier scheme".to_string());
+    }
+    let mapping = status.wordnet.map_ids(from, to, &[id.to_string()])
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&mapping[0])
+        .map_err(|e| format!("Failed to serialize mapping: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The relation types, with their inverses, parts of speech and labels
+#[get("/json/relations")]
+fn relation_types() -> Result<RawJson<String>, String> {
+    let json = serde_json::to_string(&relations::registry().types)
+        .map_err(|e| format!("Failed to serialize relations: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The relations that point to a synset, including those that are not
+/// stored with an inverse
+#[get("/json/incoming/<id>?<type>")]
+fn incoming(id : &str, r#type : Option<&str>) -> Result<RawJson<String>, String> {
+    if let Some(rel_type) = r#type {
+        if relations::registry().get(rel_type).is_none() {
+            return Err(format!("Unknown relation type: {}", rel_type));
+        }
+    }
+    let status = WordNetState::get();
+    status.wordnet.get_synset(&id.to_string())
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Synset Not Found".to_string())?;
+    let relations = taxonomy::incoming(&status.wordnet, &id.to_string(), r#type)
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&relations)
+        .map_err(|e| format!("Failed to serialize relations: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The shortest chain of relations linking two synsets. The relations
+/// followed can be restricted by a comma-separated list of types
+#[get("/json/path/<id1>/<id2>?<relations>&<max_hops>")]
+fn shortest_path(id1 : &str, id2 : &str, relations : Option<&str>,
+                 max_hops : Option<usize>) -> Result<RawJson<String>, String> {
+    let rel_types : Option<Vec<&str>> = relations.map(|r| {
+        r.split(",").map(|t| t.trim()).filter(|t| !t.is_empty()).collect()
+    });
+    for rel_type in rel_types.iter().flatten() {
+        if relations::registry().get(rel_type).is_none() {
+            return Err(format!("Unknown relation type: {}", rel_type));
+        }
+    }
+    let status = WordNetState::get();
+    for id in [id1, id2].iter() {
+        status.wordnet.get_synset(&id.to_string())
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| "Synset Not Found".to_string())?;
+    }
+    let path = taxonomy::shortest_path(&status.wordnet, &id1.to_string(), &id2.to_string(),
+                                       rel_types.as_deref(),
+                                       max_hops.unwrap_or(10).min(taxonomy::MAX_DEPTH))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No path found".to_string())?;
+    let json = serde_json::to_string(&path)
+        .map_err(|e| format!("Failed to serialize path: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The name of the information content to use for a measure, `ic` (default
+/// `brown`), if the measure requires one. The information content is loaded
+/// from the files `data/ic-<name>.dat` with the wordnet
+fn information_content_name<'a>(wordnet : &WordNet, measure : Measure, ic : Option<&'a str>)
+        -> Result<Option<&'a str>, String> {
+    if !measure.needs_ic() {
+        return Ok(None);
+    }
+    let name = ic.unwrap_or("brown");
+    if !wordnet.has_information_content(name)
+            .map_err(|e| format!("Database error: {}", e))? {
+        return Err(format!("Unknown information content: {}", name));
+    }
+    Ok(Some(name))
+}
+
+#[get("/json/similarity/<measure>/<id1>/<id2>?<ic>")]
+fn synset_similarity(measure : &str, id1 : &str, id2 : &str, ic : Option<&str>)
+        -> Result<RawJson<String>, String> {
+    let measure = Measure::from_str(measure)
+        .map_err(|e| format!("{}", e))?;
+    let status = WordNetState::get();
+    let ic = information_content_name(&status.wordnet, measure, ic)?;
+    let sim = similarity::similarity(&status.wordnet, measure, &id1.to_string(),
+                                     &id2.to_string(), ic)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Synset Not Found".to_string())?;
+    let json = serde_json::to_string(&sim)
+        .map_err(|e| format!("Failed to serialize similarity: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The similarity of two lemmas by their most similar senses
+#[get("/json/lemma_similarity/<measure>/<lang>/<lemma1>/<lemma2>?<ic>")]
+fn lemma_similarity(measure : &str, lang : &str, lemma1 : &str, lemma2 : &str,
+                    ic : Option<&str>) -> Result<RawJson<String>, String> {
+    let measure = Measure::from_str(measure)
+        .map_err(|e| format!("{}", e))?;
+    let status = WordNetState::get();
+    let ic = information_content_name(&status.wordnet, measure, ic)?;
+    let sim = similarity::lemma_similarity(&status.wordnet, measure, lemma1, lemma2, lang, ic)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "No similarity found".to_string())?;
+    let json = serde_json::to_string(&sim)
+        .map_err(|e| format!("Failed to serialize similarity: {}", e))?;
+    Ok(RawJson(json))
+}
+
 #[get("/json_rel/<id>")]
 fn rel_targets(id : &str) -> Result<RawJson<String>, String> {
     let status = WordNetState::get();
@@ -352,9 +734,8 @@ fn autocomplete_lemma(index : &str, key : &str) -> Result<String, String> {
                 })
 //            }
         }   
-    } else if index.starts_with("lemma") {
-        let lang = index[6..].to_string();
-        for s in state.wordnet.list_by_lemma(key, &lang, 10).map_err(|e| format!("Database error: {}", e))? {
+    } else if let Some(lang) = omwn::lemma_language(index) {
+        for s in state.wordnet.list_by_lemma(key, lang, 10).map_err(|e| format!("Database error: {}", e))? {
 //            if s.starts_with(&key) {
                 results.push(AutocompleteResult {
                     display: s.to_string(),
@@ -392,8 +773,8 @@ fn autocomplete_lemma(index : &str, key : &str) -> Result<String, String> {
             }
         }   
      } else {
-        let key2 = autocomplete_wn_key(&key)?;
-        for s in state.wordnet.list_by_old_id(&index, &key2, 10).map_err(|e| format!("Database error: {}", e))? {
+        let index = scheme_name(&state.wordnet, index)?;
+        for s in state.wordnet.list_by_old_id(&index, &key.to_string(), 10).map_err(|e| format!("Database error: {}", e))? {
             if s.to_string().starts_with(&key) {
                 results.push(AutocompleteResult {
                     display: s.to_string(),
@@ -402,6 +783,16 @@ fn autocomplete_lemma(index : &str, key : &str) -> Result<String, String> {
             }
         }   
 }
+    if results.is_empty() {
+        if let Some(lang) = omwn::lemma_language(index) {
+            for s in state.wordnet.suggest_lemmas(key, lang, 10).map_err(|e| format!("Database error: {}", e))? {
+                results.push(AutocompleteResult {
+                    display: s.to_string(),
+                    item: s.to_string()
+                })
+            }
+        }
+    }
     serde_json::to_string(&results).map_err(|e| format!("Json error: {}", e))
 }
 
@@ -436,7 +827,7 @@ enum NegotiatedResponse {
     Html(RawHtml<&'static str>)
 }
 
-fn negotiated(idx : &'static str, key : &str, neg : ContentNegotiation) -> NegotiatedResponse {
+fn negotiated(idx : &str, key : &str, neg : ContentNegotiation) -> NegotiatedResponse {
     if key.ends_with(".rdf") {
         renegotiated(idx,&key[0..(key.len()-4)], ContentNegotiation::RdfXml)
     } else if key.ends_with(".ttl") {
@@ -463,7 +854,16 @@ fn negotiated(idx : &'static str, key : &str, neg : ContentNegotiation) -> Negot
     }
 }
 
-fn renegotiated(idx : &'static str, key : &str, neg : ContentNegotiation) -> NegotiatedResponse {
+/// The path of the page for an index, as the lemmas of each language are
+/// served at `/lemma/<lang>`
+fn page_path(idx : &str) -> String {
+    match omwn::lemma_language(idx) {
+        Some(lang) if idx != "lemma" => format!("lemma/{}", lang),
+        _ => idx.to_string()
+    }
+}
+
+fn renegotiated(idx : &str, key : &str, neg : ContentNegotiation) -> NegotiatedResponse {
     if key.ends_with(".rdf") {
         renegotiated(idx,&key[0..(key.len()-4)], ContentNegotiation::RdfXml)
     } else if key.ends_with(".ttl") {
@@ -475,7 +875,7 @@ fn renegotiated(idx : &'static str, key : &str, neg : ContentNegotiation) -> Neg
     } else {
         match neg {
             ContentNegotiation::Html => { 
-                NegotiatedResponse::Redirect(Redirect::to(format!("/{}/{}", idx, key)))
+                NegotiatedResponse::Redirect(Redirect::to(format!("/{}/{}", page_path(idx), key)))
             },
             ContentNegotiation::RdfXml => {
                 NegotiatedResponse::Redirect(Redirect::to(format!("/rdf/{}/{}", idx, key)))
@@ -494,54 +894,106 @@ fn renegotiated(idx : &'static str, key : &str, neg : ContentNegotiation) -> Neg
 
 #[get("/lemma/<key>")]
 fn lemma(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma", key, neg) }
-#[get("/lemma-en/<key>")]
-fn lemma_en(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-en", key, neg) }
-#[get("/lemma-bg/<key>")]
-fn lemma_bg(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-bg", key, neg) }
-#[get("/lemma-cs/<key>")]
-fn lemma_cs(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-cs", key, neg) }
-#[get("/lemma-da/<key>")]
-fn lemma_da(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-da", key, neg) }
-#[get("/lemma-de/<key>")]
-fn lemma_de(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-de", key, neg) }
-#[get("/lemma-el/<key>")]
-fn lemma_el(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-el", key, neg) }
-#[get("/lemma-es/<key>")]
-fn lemma_es(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-es", key, neg) }
-#[get("/lemma-et/<key>")]
-fn lemma_et(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-et", key, neg) }
-#[get("/lemma-fi/<key>")]
-fn lemma_fi(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-fi", key, neg) }
-#[get("/lemma-fr/<key>")]
-fn lemma_fr(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-fr", key, neg) }
-#[get("/lemma-ga/<key>")]
-fn lemma_ga(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-ga", key, neg) }
-#[get("/lemma-hr/<key>")]
-fn lemma_hr(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-hr", key, neg) }
-#[get("/lemma-hu/<key>")]
-fn lemma_hu(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-hu", key, neg) }
-#[get("/lemma-it/<key>")]
-fn lemma_it(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-it", key, neg) }
-#[get("/lemma-lt/<key>")]
-fn lemma_lt(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-lt", key, neg) }
-#[get("/lemma-lv/<key>")]
-fn lemma_lv(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-lv", key, neg) }
-#[get("/lemma-mt/<key>")]
-fn lemma_mt(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-mt", key, neg) }
-#[get("/lemma-nl/<key>")]
-fn lemma_nl(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-nl", key, neg) }
-#[get("/lemma-pl/<key>")]
-fn lemma_pl(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-pl", key, neg) }
-#[get("/lemma-pt/<key>")]
-fn lemma_pt(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-pt", key, neg) }
-#[get("/lemma-ro/<key>")]
-fn lemma_ro(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-ro", key, neg) }
-#[get("/lemma-sk/<key>")]
-fn lemma_sk(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-sk", key, neg) }
-#[get("/lemma-sl/<key>")]
-fn lemma_sl(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-sl", key, neg) }
-#[get("/lemma-sv/<key>")]
-fn lemma_sv(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("lemma-sv", key, neg) }
+
+/// A lemma in one of the languages in the database, e.g., `/lemma/fr/chat`
+#[get("/lemma/<lang>/<key>")]
+fn lemma_lang(lang : &str, key : &str, neg : ContentNegotiation) -> Option<NegotiatedResponse> {
+    let lang = omwn::language_code(lang);
+    let known = WordNetState::get().wordnet.has_language(lang).ok()?;
+    if known {
+        Some(negotiated(&format!("lemma-{}", lang), key, neg))
+    } else {
+        None
+    }
+}
+
+/// Translate a word through the senses it shares with the target language
+#[get("/json/translate/<from>/<to>/<word>")]
+fn translate_word(from : &str, to : &str, word : &str) -> Result<RawJson<String>, String> {
+    let from = omwn::language_code(from);
+    let to = omwn::language_code(to);
+    let status = WordNetState::get();
+    for lang in [from, to].iter() {
+        if !status.wordnet.has_language(lang)
+                .map_err(|e| format!("Database error: {}", e))? {
+            return Err(format!("Unknown language: {}", lang));
+        }
+    }
+    let translations = translate::translate(&status.wordnet, word, from, to)
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&translations)
+        .map_err(|e| format!("Failed to serialize translations: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The coverage of the synsets by each language, with the uncovered synsets
+/// that have the most hyponyms. As this requires a scan of all the lemmas,
+/// the result is computed once
+#[get("/json/stats/coverage?<limit>")]
+fn stats_coverage(limit : Option<u32>) -> Result<RawJson<String>, String> {
+    let limit = limit.unwrap_or(20).min(1000);
+    let mut cache = WORDNETSTATE_COVERAGE.lock().unwrap();
+    let json = match cache.entry(limit) {
+        Entry::Occupied(e) => e.get().clone(),
+        Entry::Vacant(e) => {
+            let core = coverage::core_synsets()
+                .map_err(|e| format!("Could not load core synsets: {}", e))?;
+            let status = WordNetState::get();
+            let result = status.wordnet.coverage(core.as_deref(), limit)
+                .map_err(|e| format!("Database error: {}", e))?;
+            let json = serde_json::to_string(&result)
+                .map_err(|e| format!("Failed to serialize coverage: {}", e))?;
+            e.insert(json).clone()
+        }
+    };
+    Ok(RawJson(json))
+}
+
+/// The synsets whose glosses use a sense of this synset, with the word and
+/// the gloss it occurs in
+#[get("/json/gloss_uses/<id>")]
+fn gloss_uses(id : &str) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let uses = status.wordnet.get_gloss_uses(&id.to_string())
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&uses)
+        .map_err(|e| format!("Failed to serialize gloss uses: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// A VerbNet class, with its roles, frames and the synsets of its members
+#[get("/json/verbnet/<class>")]
+fn verbnet_class(class : &str) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let result = status.wordnet.get_verbnet_class(class)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Unknown VerbNet class: {}", class))?;
+    let json = serde_json::to_string(&result)
+        .map_err(|e| format!("Failed to serialize VerbNet class: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The VerbNet classes of a verb synset
+#[get("/json/verbnet_classes/<id>")]
+fn verbnet_classes(id : &str) -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let classes = status.wordnet.get_verbnet_classes(&id.to_string())
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&classes)
+        .map_err(|e| format!("Failed to serialize VerbNet classes: {}", e))?;
+    Ok(RawJson(json))
+}
+
+/// The languages in the database with the number of lemmas and senses
+#[get("/json/languages")]
+fn languages() -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let languages = status.wordnet.get_languages()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&languages)
+        .map_err(|e| format!("Failed to serialize languages: {}", e))?;
+    Ok(RawJson(json))
+}
 
 #[get("/id/<key>")]
 fn id(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("id", key, neg) }
@@ -549,18 +1001,39 @@ fn id(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("
 fn ili(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("ili", key, neg) }
 #[get("/sense_key/<key>")]
 fn sense_key(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("sense_key", key, neg) }
-#[get("/pwn30/<key>")]
-fn pwn30(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn30", key, neg) }
-#[get("/pwn21/<key>")]
-fn pwn21(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn21", key, neg) }
-#[get("/pwn20/<key>")]
-fn pwn20(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn20", key, neg) }
-#[get("/pwn171/<key>")]
-fn pwn171(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn171", key, neg) }
-#[get("/pwn17/<key>")]
-fn pwn17(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn17", key, neg) }
-#[get("/pwn16/<key>")]
-fn pwn16(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { negotiated("pwn16", key, neg) }
+
+/// An identifier in one of the schemes loaded from the mapping files, e.g.,
+/// `/pwn30/00001740-a`. The Princeton WordNet schemes were served at
+/// `/wn30/<key>` etc., which still redirect to the scheme
+#[get("/<prefix>/<key>", rank = 10)]
+fn scheme(prefix : &str, key : &str, neg : ContentNegotiation) -> Option<NegotiatedResponse> {
+    // Lemmas used to be served at `/lemma-<lang>/<key>`
+    if omwn::lemma_language(prefix).is_some() {
+        return Some(NegotiatedResponse::Redirect(
+                Redirect::to(format!("/{}/{}", page_path(prefix), key))));
+    }
+    let schemes = WordNetState::get().wordnet.get_schemes().ok()?;
+    if schemes.iter().any(|s| s.prefix == prefix) {
+        Some(negotiated(prefix, key, neg))
+    } else if prefix.starts_with("wn") {
+        let name = format!("p{}", prefix);
+        schemes.iter().find(|s| s.name == name)
+            .map(|s| renegotiated(&s.prefix, key, neg))
+    } else {
+        None
+    }
+}
+
+/// The identifier schemes and the prefixes they are served at
+#[get("/json/schemes")]
+fn id_schemes() -> Result<RawJson<String>, String> {
+    let status = WordNetState::get();
+    let schemes = status.wordnet.get_schemes()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let json = serde_json::to_string(&schemes)
+        .map_err(|e| format!("Failed to serialize schemes: {}", e))?;
+    Ok(RawJson(json))
+}
 
 #[get("/english-wordnet-2019.ttl.gz")]
 fn ewn2019ttl() -> Option<(ContentType, File)> {
@@ -609,18 +1082,6 @@ fn wn31(key : &str, neg : ContentNegotiation) -> NegotiatedResponse {
         renegotiated("lemma", &key[..(key.len()-2)], neg)
     }
 }
-#[get("/wn30/<key>")]
-fn wn30(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn30", key, neg) }
-#[get("/wn21/<key>")]
-fn wn21(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn21", key, neg) }
-#[get("/wn20/<key>")]
-fn wn20(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn20", key, neg) }
-#[get("/wn171/<key>")]
-fn wn171(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn171", key, neg) }
-#[get("/wn17/<key>")]
-fn wn17(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn17", key, neg) }
-#[get("/wn16/<key>")]
-fn wn16(key : &str, neg : ContentNegotiation) -> NegotiatedResponse { renegotiated("pwn16", key, neg) }
 #[get("/wn31.nt.gz")]
 fn wn31ntgz() -> Redirect {
     Redirect::to("/static/wordnet.nt.gz")
@@ -667,6 +1128,7 @@ fn ontology_html() -> RawHtml<&'static str> {
 #[derive(Clone)]
 struct Config {
     wn_file : String,
+    glosstags : Option<String>,
     reload : bool,
     port : u16,
     site : WordNetSite
@@ -685,6 +1147,7 @@ impl Config {
         };
         Ok(Config {
             wn_file: wn_file.to_string(),
+            glosstags: matches.value_of("glosstags").map(|s| s.to_string()),
             reload: matches.is_present("reload"),
             port,
             site
@@ -695,6 +1158,7 @@ impl Config {
 static WORDNETSTATE_WORDNET: Lazy<Mutex<WordNet>> = Lazy::new(|| Mutex::new(WordNet::new()));
 static WORDNETSTATE_HANDLEBARS: Lazy<Mutex<Handlebars>> = Lazy::new(|| Mutex::new(Handlebars::new()));
 static WORDNETSTATE_SITE: Lazy<Mutex<WordNetSite>> = Lazy::new(|| Mutex::new(WordNetSite::Princeton));
+static WORDNETSTATE_COVERAGE: Lazy<Mutex<HashMap<u32, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 struct WordNetState<'a> {
     wordnet: MutexGuard<'a, WordNet>,
@@ -780,13 +1244,13 @@ fn prepare_server(config : Config) -> Result<(), String> {
     let wordnet = if config.reload  {
         eprintln!("Loading WordNet data");
         if config.site == WordNetSite::Princeton {
-            wordnet_read::load_pwn(config.wn_file)
+            wordnet_read::load_pwn(config.wn_file, config.glosstags.as_deref())
                 .map_err(|e| format!("Failed to load WordNet: {}", e))?
         } else if config.site == WordNetSite::English {
-            wordnet_read::load_enwn(config.wn_file)
+            wordnet_read::load_enwn(config.wn_file, config.glosstags.as_deref())
                 .map_err(|e| format!("Failed to load WordNet: {}", e))?
         } else {
-            wordnet_read::load_gwn(config.wn_file)
+            wordnet_read::load_gwn(config.wn_file, config.glosstags.as_deref())
                 .map_err(|e| format!("Failed to load WordNet: {}", e))?
         }
     } else {
@@ -883,6 +1347,11 @@ fn rocket() -> _ {
             .long("wn")
             .value_name("wn31.xml")
             .help("The WordNet file in GWC LMF-XML format, e.g., http://john.mccr.ae/wn31.xml. Default is data/wn31.xml")
+            .takes_value(true))
+        .arg(Arg::with_name("glosstags")
+            .long("glosstags")
+            .value_name("data/merged")
+            .help("The directory of the Princeton gloss tag corpus to load with --reload")
             .takes_value(true));
     let matches = app.clone().get_matches();
     match Config::new(&matches) {
@@ -898,19 +1367,19 @@ fn rocket() -> _ {
                         .mount("/", routes![
                                 about, ontology, ontology_html, license,
                                 get_xml, get_ttl, get_rdf, rel_targets,
-                                index, synset, get_flag,
+                                index, synset, suggest, get_flag, pattern_search,
+                                rhymes, homophones, ipa_search,
+                                hypernym_paths, hyponym_tree, closure,
+                                synset_similarity, lemma_similarity,
+                                shortest_path, incoming, relation_types,
+                                batch, map_id,
                                 autocomplete_lemma, get_static,
-                                lemma_bg, lemma_cs, lemma_da, lemma_de,
-                                lemma_el, lemma_en, lemma_es, lemma_et,
-                                lemma_fi, lemma_fr, lemma_ga, lemma_hr,
-                                lemma_hu, lemma_it, lemma_lt, lemma_lv,
-                                lemma_mt, lemma_nl, lemma_pl, lemma_pt,
-                                lemma_ro, lemma_sk, lemma_sl, lemma_sv,
+                                lemma_lang, languages, translate_word,
+                                stats_coverage, gloss_uses,
+                                verbnet_class, verbnet_classes,
                                 lemma, id, ili, sense_key, 
-                                wn30, wn21, wn20, wn17,
-                                wn171, wn16, wn31, wn31ntgz,
-                                pwn30, pwn21, pwn20, pwn17,
-                                pwn171, pwn16, ewn2019zip, 
+                                wn31, wn31ntgz,
+                                scheme, id_schemes, ewn2019zip, 
                                 ewn2019xml, ewn2019ttl])
                 },
                 Err(msg) => {
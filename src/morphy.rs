@@ -0,0 +1,92 @@
+//! Morphological processing of inflected (English) words, following the
+//! `morphy` algorithm of Princeton WordNet: irregular forms are looked up in
+//! the exception lists and regular forms are reduced by detachment rules
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead,BufReader};
+use crate::wordnet::WordNetLoadError;
+
+/// The exception lists of Princeton WordNet and the part of speech they
+/// apply to
+pub const EXCEPTION_FILES : [(&str, &str); 4] = [
+    ("n", "data/dict/noun.exc"),
+    ("v", "data/dict/verb.exc"),
+    ("a", "data/dict/adj.exc"),
+    ("r", "data/dict/adv.exc")
+];
+
+const NOUN_RULES : [(&str, &str); 9] = [
+    ("s", ""), ("ses", "s"), ("xes", "x"), ("zes", "z"),
+    ("ches", "ch"), ("shes", "sh"), ("men", "man"), ("ies", "y"),
+    ("ful", "")
+];
+
+const VERB_RULES : [(&str, &str); 8] = [
+    ("s", ""), ("ies", "y"), ("es", "e"), ("es", ""),
+    ("ed", "e"), ("ed", ""), ("ing", "e"), ("ing", "")
+];
+
+const ADJ_RULES : [(&str, &str); 4] = [
+    ("er", ""), ("est", ""), ("er", "e"), ("est", "e")
+];
+
+fn rules(pos : &str) -> &'static [(&'static str, &'static str)] {
+    match pos {
+        "n" => &NOUN_RULES,
+        "v" => &VERB_RULES,
+        "a" => &ADJ_RULES,
+        _ => &[]
+    }
+}
+
+/// The candidate base forms of a word obtained by the detachment rules for
+/// a part of speech (one of `n`, `v`, `a` or `r`)
+pub fn detach(form : &str, pos : &str) -> Vec<String> {
+    let mut result : Vec<String> = Vec::new();
+    for &(suffix, ending) in rules(pos) {
+        if form.len() > suffix.len() && form.ends_with(suffix) {
+            let base = format!("{}{}", &form[..(form.len() - suffix.len())], ending);
+            if !result.contains(&base) {
+                result.push(base);
+            }
+        }
+    }
+    result
+}
+
+/// Read a Princeton WordNet exception list, where each line consists of an
+/// inflected form followed by one or more base forms. Underscores are
+/// replaced by spaces
+pub fn read_exceptions<P : AsRef<Path>>(path : P)
+        -> Result<Vec<(String, String)>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        let mut elems = line.split_whitespace();
+        if let Some(form) = elems.next() {
+            for base in elems {
+                result.push((form.replace("_", " "), base.replace("_", " ")));
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detach() {
+        assert_eq!(detach("boxes", "n"), vec!["boxe", "box"]);
+        assert_eq!(detach("ponies", "n"), vec!["ponie", "pony"]);
+        assert_eq!(detach("handful", "n"), vec!["hand"]);
+        assert_eq!(detach("women", "n"), vec!["woman"]);
+        assert_eq!(detach("hoped", "v"), vec!["hope", "hop"]);
+        assert_eq!(detach("cries", "v"), vec!["crie", "cry", "cri"]);
+        assert_eq!(detach("taller", "a"), vec!["tall", "talle"]);
+        assert_eq!(detach("s", "n"), Vec::<String>::new());
+        assert_eq!(detach("quickly", "r"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,135 @@
+//! Normalisation of lemmas to the keys used for lookup. Each language has a
+//! set of folding rules that are applied both when the lemmas are indexed
+//! and when they are queried
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead,BufReader};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use crate::wordnet::WordNetLoadError;
+
+/// The file that configures the folding rules for each language
+pub const FOLDING_FILE : &str = "data/folding.tab";
+
+/// The rules used to fold a lemma to its lookup key
+#[derive(Clone,Debug,PartialEq)]
+pub struct Folding {
+    /// Apply compatibility normalisation (NFKC) instead of NFC
+    pub compatibility : bool,
+    /// Ignore case
+    pub case : bool,
+    /// Ignore diacritics, e.g., "café" is looked up as "cafe"
+    pub diacritics : bool
+}
+
+impl Default for Folding {
+    fn default() -> Folding {
+        Folding { compatibility: false, case: true, diacritics: false }
+    }
+}
+
+impl Folding {
+    /// Parse a comma-separated list of rules, e.g., `nfkc,case,diacritics`
+    pub fn from_rules(rules : &str) -> Result<Folding, WordNetLoadError> {
+        let mut folding = Folding { compatibility: false, case: false, diacritics: false };
+        for rule in rules.split(",").map(|r| r.trim()).filter(|r| !r.is_empty()) {
+            match rule {
+                "nfc" => folding.compatibility = false,
+                "nfkc" => folding.compatibility = true,
+                "case" => folding.case = true,
+                "diacritics" => folding.diacritics = true,
+                _ => return Err(WordNetLoadError::Schema("Bad folding rule"))
+            }
+        }
+        Ok(folding)
+    }
+
+    /// The rules as a comma-separated list, as accepted by `from_rules`
+    pub fn to_rules(&self) -> String {
+        let mut rules = vec![if self.compatibility { "nfkc" } else { "nfc" }];
+        if self.case {
+            rules.push("case");
+        }
+        if self.diacritics {
+            rules.push("diacritics");
+        }
+        rules.join(",")
+    }
+
+    /// Fold a string to its lookup key
+    pub fn fold(&self, s : &str) -> String {
+        let s : String = if self.diacritics {
+            let decomposed : String = if self.compatibility {
+                s.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+            } else {
+                s.nfd().filter(|c| !is_combining_mark(*c)).collect()
+            };
+            decomposed.nfc().collect()
+        } else if self.compatibility {
+            s.nfkc().collect()
+        } else {
+            s.nfc().collect()
+        };
+        if self.case {
+            s.to_lowercase()
+        } else {
+            s
+        }
+    }
+}
+
+/// The folding rules used if `data/folding.tab` is not given, which ignore
+/// diacritics in English, so that "cafe" finds "Café"
+pub fn default_folding() -> HashMap<String, Folding> {
+    let mut result = HashMap::new();
+    result.insert("en".to_string(),
+        Folding { compatibility: false, case: true, diacritics: true });
+    result
+}
+
+/// Read the folding rules for each language. Each line consists of a
+/// language code and a comma-separated list of rules
+pub fn read_folding<P : AsRef<Path>>(path : P)
+        -> Result<HashMap<String, Folding>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = HashMap::new();
+    for line in file.lines() {
+        let line = line?;
+        if !line.starts_with("#") && !line.is_empty() {
+            let mut elems = line.split("\t");
+            if let Some(lang) = elems.next() {
+                result.insert(lang.to_string(),
+                    Folding::from_rules(elems.next().unwrap_or(""))?);
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold() {
+        let default = Folding::default();
+        assert_eq!(default.fold("Café"), "café");
+        assert_eq!(default.fold("Cafe\u{301}"), "café");
+        assert_eq!(default.fold("ﬁle"), "ﬁle");
+        let all = Folding::from_rules("nfkc,case,diacritics").unwrap();
+        assert_eq!(all.fold("Café"), "cafe");
+        assert_eq!(all.fold("ﬁle"), "file");
+        let none = Folding::from_rules("nfc").unwrap();
+        assert_eq!(none.fold("Café"), "Café");
+    }
+
+    #[test]
+    fn test_rules() {
+        let folding = Folding::from_rules("nfkc, diacritics").unwrap();
+        assert_eq!(folding, Folding { compatibility: true, case: false, diacritics: true });
+        assert_eq!(folding.to_rules(), "nfkc,diacritics");
+        assert_eq!(Folding::default().to_rules(), "nfc,case");
+        assert!(Folding::from_rules("accents").is_err());
+    }
+}
@@ -4,40 +4,156 @@ use std::collections::HashMap;
 use std::io::{BufRead,BufReader};
 use crate::wordnet::{WNKey, WordNetLoadError, WordNetBuilder};
 
+/// The two-letter codes of the languages of the Open Multilingual Wordnet,
+/// which are used for the lemmas in the database. Languages that have no
+/// two-letter code keep their three-letter code
+const LANGUAGE_CODES : [(&str, &str); 30] = [
+    ("als", "sq"), ("arb", "ar"), ("bul", "bg"), ("cmn", "zh"),
+    ("ell", "el"), ("fas", "fa"), ("fin", "fi"), ("fra", "fr"),
+    ("heb", "he"), ("hrv", "hr"), ("isl", "is"), ("ita", "it"),
+    ("jpn", "ja"), ("cat", "ca"), ("eus", "eu"), ("glg", "gl"),
+    ("spa", "es"), ("ind", "id"), ("zsm", "ms"), ("nld", "nl"),
+    ("nno", "nn"), ("nob", "nb"), ("pol", "pl"), ("por", "pt"),
+    ("ron", "ro"), ("slk", "sk"), ("lit", "lt"), ("slv", "sl"),
+    ("swe", "sv"), ("tha", "th")
+];
+
+/// The language code used for the lemmas of an OMW language
+pub fn language_code(lang : &str) -> &str {
+    LANGUAGE_CODES.iter()
+        .find(|(l, _)| *l == lang)
+        .map(|(_, code)| *code)
+        .unwrap_or(lang)
+}
+
+/// The language of a lemma index, e.g., `lemma` or `lemma-fr`. The
+/// three-letter codes of the Open Multilingual Wordnet are also accepted
+pub fn lemma_language(index : &str) -> Option<&str> {
+    if index == "lemma" {
+        Some("en")
+    } else {
+        index.strip_prefix("lemma-").map(language_code)
+    }
+}
+
+/// The file that lists the OMW languages to load and their projects
+pub const OMW_LANGUAGES_FILE : &str = "data/omw-languages.tab";
+
+/// The languages loaded if there is no configuration file, with the project
+/// they are found in under `data/wns` if it differs from the language
+const DEFAULT_LANGUAGES : [(&str, &str); 31] = [
+    ("als", "als"), ("arb", "arb"), ("bul", "bul"), ("cmn", "cow"),
+    ("qcn", "cwn"), ("ell", "ell"), ("fas", "fas"), ("fin", "fin"),
+    ("fra", "fra"), ("heb", "heb"), ("hrv", "hrv"), ("isl", "isl"),
+    ("ita", "ita"), ("jpn", "jpn"), ("cat", "mcr"), ("eus", "mcr"),
+    ("glg", "mcr"), ("spa", "mcr"), ("ind", "msa"), ("zsm", "msa"),
+    ("nld", "nld"), ("nno", "nor"), ("nob", "nor"), ("pol", "pol"),
+    ("por", "por"), ("ron", "ron"), ("slk", "slk"), ("lit", "slk"),
+    ("slv", "slv"), ("swe", "swe"), ("tha", "tha")
+];
+
+/// A language of the Open Multilingual Wordnet and the project that
+/// provides it
+#[derive(Clone,Debug)]
+pub struct OmwLanguage {
+    pub language : String,
+    pub project : String
+}
+
+impl OmwLanguage {
+    /// The data file of this language
+    pub fn path(&self) -> String {
+        format!("data/wns/{}/wn-data-{}.tab", self.project, self.language)
+    }
+}
+
+pub fn default_languages() -> Vec<OmwLanguage> {
+    DEFAULT_LANGUAGES.iter().map(|&(language, project)| OmwLanguage {
+        language: language.to_string(),
+        project: project.to_string()
+    }).collect()
+}
+
+/// Read the OMW languages to load, where each line consists of a language
+/// code and the project
+pub fn read_languages<P : AsRef<Path>>(path : P) -> Result<Vec<OmwLanguage>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if !line.starts_with("#") && !line.is_empty() {
+            let elems : Vec<&str> = line.split("\t").collect();
+            if elems.len() != 2 {
+                return Err(WordNetLoadError::Schema("Bad line in OMW languages file"));
+            }
+            result.push(OmwLanguage {
+                language: elems[0].to_string(),
+                project: elems[1].to_string()
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// The lemmas, definitions and examples of a language, by synset
+#[derive(Default)]
+pub struct OmwData {
+    pub lemmas : HashMap<WNKey, Vec<String>>,
+    pub definitions : HashMap<WNKey, Vec<String>>,
+    pub examples : HashMap<WNKey, Vec<String>>
+}
+
+/// Load an OMW tab file. Each line consists of a Princeton WordNet 3.0 key,
+/// a type such as `fra:lemma`, `fra:def` or `fra:exe` and the value, where
+/// definitions and examples may have a sequence number before the value
 pub fn load_omwn<P: AsRef<Path>>(p : P, wordnet : &WordNetBuilder)
-        -> Result<HashMap<WNKey, Vec<String>>, WordNetLoadError> {
+        -> Result<OmwData, WordNetLoadError> {
     let file = BufReader::new(File::open(p)?);
-    let mut result = HashMap::new();
+    let mut result = OmwData::default();
+    let mut numbered : HashMap<(WNKey, bool), Vec<(u32, String)>> = HashMap::new();
     for line in file.lines () {
         let line = line?;
-        let mut elems = line.split("\t");
         if !line.starts_with("#") && line.len() > 0 {
-            match elems.next() {
-                Some(wn30) => {
-                   //let wn30key = WNKey::from_str(wn30)?;
-                   let wn30key = wn30.to_string();
-                   elems.next().map(|t| {
-                        elems.next().map(|v| {
-                            if t.ends_with("lemma") {
-                                wordnet.get_id_by_pwn30(&wn30key)
-                                  .expect("Need WordNet 3.0 mapping to load OMWN")
-                                  .map(|id| {
-                                    result.entry(id.clone())
-                                        .or_insert_with(|| Vec::new())
-                                        .push(v.to_string());
-                                });
-                            } 
-                        })
-                    });
-                },
-                None => {}
+            let elems : Vec<&str> = line.split("\t").collect();
+            if elems.len() < 3 {
+                continue;
+            }
+            let wn30key = elems[0].to_string();
+            let t = elems[1];
+            let id = match wordnet.get_id_by_pwn30(&wn30key)
+                .expect("Need WordNet 3.0 mapping to load OMWN") {
+                Some(id) => id,
+                None => continue
+            };
+            if t.ends_with("lemma") {
+                result.lemmas.entry(id)
+                    .or_insert_with(Vec::new)
+                    .push(elems[2].to_string());
+            } else if t.ends_with(":def") || t.ends_with(":exe") {
+                let (n, v) = if elems.len() > 3 {
+                    (elems[2].parse().unwrap_or(0), elems[3])
+                } else {
+                    (0, elems[2])
+                };
+                numbered.entry((id, t.ends_with(":def")))
+                    .or_default()
+                    .push((n, v.to_string()));
             }
         }
     }
-    for (_, v) in result.iter_mut() {
+    for (_, v) in result.lemmas.iter_mut() {
         v.sort();
         v.dedup();
     }
+    for ((id, is_def), mut v) in numbered.into_iter() {
+        v.sort();
+        v.dedup();
+        let values = v.into_iter().map(|(_, s)| s).collect();
+        if is_def {
+            result.definitions.insert(id, values);
+        } else {
+            result.examples.insert(id, values);
+        }
+    }
     Ok(result)
 }
-
@@ -4,42 +4,55 @@ angular.module('app', ['ngMaterial'])
     });
 
 
+// The flags shown for the languages, by language code
+var flags = {
+    bg: 'bul', hr: 'hrv', cs: 'cze', da: 'dnk', nl: 'nld', en: 'eng',
+    et: 'est', fi: 'fin', fr: 'fra', de: 'deu', el: 'ell', hu: 'hun',
+    ga: 'irl', it: 'ita', lv: 'lva', lt: 'lit', mt: 'mlt', pl: 'pol',
+    pt: 'por', ro: 'ron', sk: 'slk', sl: 'slv', es: 'spa', sv: 'swe'
+};
+
+// The English name of a language, or the code if it is not known
+function languageName(code) {
+    try {
+        return new Intl.DisplayNames(['en'], { type: 'language' }).of(code) || code;
+    } catch(e) {
+        return code;
+    }
+}
+
+// The codes of the languages in the database
+function getLanguages($http) {
+    return $http.get("/json/languages", { cache: true }).then(
+        function(result) {
+            return result.data.map((l) => l.language);
+        });
+}
+
 angular.module('app').component('wordnet', {
     templateUrl: 'static/wordnet.html',
-    controller: function($scope) {
+    controller: function($scope, $http) {
         $scope.synsets = [];
         $scope.focus = "";
         $scope.display = {
             show_wn31: true,
             display: false,
             langs: false,
-            lang_en: true
+            lang_en: true,
+            language: 'en'
         };
+        $scope.flags = flags;
+        $scope.languageName = languageName;
+        $scope.languages = [];
+        getLanguages($http).then(function(languages) {
+            $scope.languages = languages;
+        }, function(response) {
+            console.log(response.data);
+        });
         $scope.selectAllPolyWN = function() {
-            $scope.display.lang_en = true;
-            $scope.display.lang_hr = true;
-            $scope.display.lang_fr = true;
-            $scope.display.lang_lv = true;
-            $scope.display.lang_mt = true;
-            $scope.display.lang_ga = true;
-            $scope.display.lang_pt = true;
-            $scope.display.lang_cs = true;
-            $scope.display.lang_el = true;
-            $scope.display.lang_it = true;
-            $scope.display.lang_nl = true;
-            $scope.display.lang_bg = true;
-            $scope.display.lang_et = true;
-            $scope.display.lang_de = true;
-            $scope.display.lang_sv = true;
-            $scope.display.lang_da = true;
-            $scope.display.lang_sk = true;
-            $scope.display.lang_es = true;
-            $scope.display.lang_fi = true;
-            $scope.display.lang_ro = true;
-            $scope.display.lang_lt = true;
-            $scope.display.lang_sl = true;
-            $scope.display.lang_hu = true;
-            $scope.display.lang_pl = true;
+            $scope.languages.forEach((lang) => {
+                $scope.display['lang_' + lang] = true;
+            });
         };
     }
 });
@@ -52,11 +65,12 @@ angular.module('app').controller('SearchController',
     var m = $location.path().match("/(.*)/(.*)");
     self.selectedItemChange = function(item) {
         if(item) {
-            $location.path("/"+ self.index + "/"+item.item);
+            $location.path("/"+ self.index.replace(/^lemma-/, "lemma/") + "/"+item.item);
             if(self.index.startsWith("lemma-")) {
                 var lang = self.index.substring(6);
                 // This is quite an ugly hack
                 eval("$scope.display.lang_" + lang + " = true;");
+                $scope.display.language = lang;
             }
             $scope.$parent.focus = item.item;
             $http.get("/json/"+ self.index + "/"+item.item).then(
@@ -66,6 +80,18 @@ angular.module('app').controller('SearchController',
                 }, function(response) {
                     console.log(response.data);
                 });
+            $scope.$parent.suggestions = [];
+            $scope.$parent.base_forms = [];
+            if(self.index == "lemma" || self.index.startsWith("lemma-")) {
+                var suggestLang = self.index == "lemma" ? "en" : self.index.substring(6);
+                $http.get("/json/suggest/" + suggestLang + "/" + item.item).then(
+                    function(result) {
+                        $scope.$parent.suggestions = result.data.suggestions;
+                        $scope.$parent.base_forms = result.data.base_forms;
+                    }, function(response) {
+                        console.log(response.data);
+                    });
+            }
         } else {
             $scope.$parent.focus = "";
             $scope.$parent.synsets = [];
@@ -74,7 +100,7 @@ angular.module('app').controller('SearchController',
     };
 
     if(m) {
-        self.index = m[1];
+        self.index = m[1].replace(/^lemma\//, "lemma-");
         if(m[2].endsWith(".html")) {
             self.selectedItem = { item: m[2].substring(0, m[2].length - 5), display: m[2] };
         } else {
@@ -106,12 +132,36 @@ angular.module('app').component('synset', {
         controller: function($http) {
             var ctrl = this;
             ctrl.targetsynsets = [];
+            ctrl.languageName = languageName;
+            // English is always shown first
+            ctrl.languages = [];
+            getLanguages($http).then(function(languages) {
+                ctrl.languages = languages.filter((lang) => lang !== 'en');
+            }, function(response) { /*alert(response);*/ });
             $http.get("/json_rel/" + this.synset.id).then(
                     function(response) {
                         ctrl.targetsynsets = response.data.slice(0,25);
                         ctrl.targetsynsetsextra = response.data.slice(25,response.data.length);
                     }, function(response) { /*alert(response);*/ }
             );
+            // The OMW definitions or examples of a language, which are
+            // given by the three-letter code of the language
+            ctrl.foreign = function(field, lang) {
+                var result = [];
+                angular.forEach(this.synset[field] || {}, (values, omwLang) => {
+                    if(Intl.getCanonicalLocales(omwLang)[0] === lang) {
+                        result = result.concat(values);
+                    }
+                });
+                return result;
+            };
+            // The Wikipedia article in the language being viewed, or else
+            // the English article
+            ctrl.wikipedia = function() {
+                var articles = (this.synset.links || []).filter((link) => link.link_type === 'Wikipedia');
+                return articles.find((link) => link.language === this.display.language) ||
+                    articles.find((link) => link.language === 'en');
+            };
             ctrl.hasSubcats = function() {
                 for(i = 0; i < this.synset.lemmas.length; i++) {
                     if(this.synset.lemmas[i].subcats.length > 0) {
@@ -0,0 +1,67 @@
+//! Functions for indexing and comparing IPA pronunciations
+
+/// The characters that are treated as vowels when finding the rhyme of a
+/// pronunciation
+const VOWELS : &str = "aeiouyæɑɒɐəɚɛɜɝɞɪɨʉɔʊʌɤɯøœɶɘɵʏ";
+
+/// A lemma found by a search over pronunciations
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct PronunciationMatch {
+    pub lemma : String,
+    pub value : String,
+    pub variety : Option<String>
+}
+
+/// Clean a pronunciation for comparison by removing the slashes or brackets
+/// around it and any whitespace
+pub fn clean(ipa : &str) -> String {
+    ipa.chars()
+        .filter(|c| !c.is_whitespace() && *c != '/' && *c != '[' && *c != ']')
+        .collect()
+}
+
+/// The rhyme of a pronunciation, that is the part from the vowel of the last
+/// stressed syllable to the end (or from the last vowel if no stress is
+/// marked). Stress marks and syllable breaks are not part of the rhyme
+pub fn rhyme(ipa : &str) -> String {
+    let ipa = clean(ipa);
+    let chars : Vec<char> = ipa.chars().collect();
+    let start = match chars.iter().rposition(|c| *c == 'ˈ') {
+        Some(i) => i + 1,
+        None => {
+            // Without stress, rhyme from the last vowel group
+            let mut i = chars.len();
+            while i > 0 && !VOWELS.contains(chars[i-1]) {
+                i -= 1;
+            }
+            while i > 0 && VOWELS.contains(chars[i-1]) {
+                i -= 1;
+            }
+            i
+        }
+    };
+    chars[start..].iter()
+        .skip_while(|c| !VOWELS.contains(**c))
+        .filter(|c| **c != 'ˈ' && **c != 'ˌ' && **c != '.')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        assert_eq!(clean("/kæt/"), "kæt");
+        assert_eq!(clean("[ kæt ]"), "kæt");
+    }
+
+    #[test]
+    fn test_rhyme() {
+        assert_eq!(rhyme("/kæt/"), "æt");
+        assert_eq!(rhyme("/ˈhæt/"), "æt");
+        assert_eq!(rhyme("/beɪ/"), "eɪ");
+        assert_eq!(rhyme("/ɪnˈvɛs.tɪˌɡeɪt/"), "ɛstɪɡeɪt");
+        assert_eq!(rhyme("/ʃ/"), "");
+    }
+}
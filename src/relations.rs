@@ -0,0 +1,188 @@
+//! The vocabulary of relation types, derived from the WordNet RDF ontology,
+//! giving the inverse, symmetry, allowed parts of speech and labels of each
+//! type
+use std::collections::{BTreeMap,HashMap};
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead,BufReader};
+use once_cell::sync::Lazy;
+use xml::reader::{EventReader, XmlEvent};
+use crate::wordnet::WordNetLoadError;
+
+/// The file that gives labels for relation types in other languages
+pub const RELATION_LABELS_FILE : &str = "data/relation-labels.tab";
+
+const ONTOLOGY_NS : &str = "http://wordnet-rdf.princeton.edu/ontology#";
+const OWL_NS : &str = "http://www.w3.org/2002/07/owl#";
+
+/// A type of relation between synsets or senses
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct RelationType {
+    pub name : String,
+    pub inverse : Option<String>,
+    pub symmetric : bool,
+    pub transitive : bool,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub pos : Vec<(String, String)>,
+    pub labels : BTreeMap<String, String>
+}
+
+impl RelationType {
+    fn new(name : &str) -> RelationType {
+        RelationType {
+            name: name.to_string(),
+            inverse: None,
+            symmetric: false,
+            transitive: false,
+            pos: Vec::new(),
+            labels: BTreeMap::new()
+        }
+    }
+
+    /// Whether this relation may link synsets of the given parts of speech
+    pub fn allows(&self, source_pos : &str, target_pos : &str) -> bool {
+        fn norm(pos : &str) -> &str {
+            if pos == "s" { "a" } else { pos }
+        }
+        self.pos.is_empty() || self.pos.iter().any(|(s, t)| {
+            s == norm(source_pos) && t == norm(target_pos)
+        })
+    }
+}
+
+/// All the known relation types, by name
+pub struct RelationRegistry {
+    pub types : BTreeMap<String, RelationType>
+}
+
+static REGISTRY : Lazy<RelationRegistry> = Lazy::new(|| {
+    let mut registry = RelationRegistry::from_ontology(include_str!("ontology.rdf"))
+        .expect("The ontology is not valid");
+    if Path::new(RELATION_LABELS_FILE).exists() {
+        if let Err(e) = registry.add_labels(RELATION_LABELS_FILE) {
+            eprintln!("Failed to load relation labels {}: {}", RELATION_LABELS_FILE, e);
+        }
+    }
+    registry
+});
+
+/// The relation types of the ontology, with any labels from
+/// `data/relation-labels.tab`
+pub fn registry() -> &'static RelationRegistry {
+    &REGISTRY
+}
+
+impl RelationRegistry {
+    /// Read the relation types from the RDF/XML ontology. These are the
+    /// properties that are declared as sub-properties of `link`. Types
+    /// without a `partOfSpeechPair` may link any parts of speech
+    pub fn from_ontology(rdf : &str) -> Result<RelationRegistry, WordNetLoadError> {
+        let mut types : HashMap<String, RelationType> = HashMap::new();
+        let mut links : Vec<String> = Vec::new();
+        let mut current : Option<String> = None;
+        let mut label_lang : Option<String> = None;
+        let mut in_pos_pair = false;
+        for e in EventReader::new(rdf.as_bytes()) {
+            match e? {
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    let resource = |local : &str| attributes.iter()
+                        .find(|a| a.name.local_name == local)
+                        .map(|a| a.value.clone());
+                    if name.local_name == "Description" {
+                        current = resource("about")
+                            .and_then(|a| a.strip_prefix(ONTOLOGY_NS).map(|s| s.to_string()));
+                    } else if let Some(ref c) = current {
+                        let t = types.entry(c.clone()).or_insert_with(|| RelationType::new(c));
+                        let target = resource("resource");
+                        match (name.local_name.as_str(), target.as_ref()) {
+                            ("subPropertyOf", Some(r)) if *r == format!("{}link", ONTOLOGY_NS) => {
+                                links.push(c.clone());
+                            },
+                            ("type", Some(r)) if *r == format!("{}SymmetricProperty", OWL_NS) => {
+                                t.symmetric = true;
+                            },
+                            ("type", Some(r)) if *r == format!("{}TransitiveProperty", OWL_NS) => {
+                                t.transitive = true;
+                            },
+                            ("inverseOf", Some(r)) => {
+                                t.inverse = r.strip_prefix(ONTOLOGY_NS).map(|s| s.to_string());
+                            },
+                            ("label", _) => {
+                                label_lang = attributes.iter()
+                                    .find(|a| a.name.local_name == "lang")
+                                    .map(|a| a.value.clone());
+                            },
+                            ("partOfSpeechPair", _) if name.namespace.as_deref() == Some(ONTOLOGY_NS) => {
+                                in_pos_pair = true;
+                            },
+                            _ => {}
+                        }
+                    }
+                },
+                XmlEvent::Characters(s) => {
+                    if let (Some(ref c), Some(ref lang)) = (&current, &label_lang) {
+                        if let Some(t) = types.get_mut(c) {
+                            t.labels.insert(lang.clone(), s);
+                        }
+                    } else if let (Some(ref c), true) = (&current, in_pos_pair) {
+                        let mut pos = s.split_whitespace();
+                        if let (Some(t), Some(source), Some(target)) =
+                            (types.get_mut(c), pos.next(), pos.next()) {
+                            t.pos.push((source.to_string(), target.to_string()));
+                        }
+                    }
+                },
+                XmlEvent::EndElement { name } => {
+                    if name.local_name == "Description" {
+                        current = None;
+                    }
+                    label_lang = None;
+                    in_pos_pair = false;
+                },
+                _ => {}
+            }
+        }
+        // Inverses are only stated in one direction in the ontology
+        let inverses : Vec<(String, String)> = types.values()
+            .filter_map(|t| t.inverse.clone().map(|i| (i, t.name.clone())))
+            .collect();
+        for (name, inverse) in inverses {
+            if let Some(t) = types.get_mut(&name) {
+                t.inverse.get_or_insert(inverse);
+            }
+        }
+        let mut result = BTreeMap::new();
+        for name in links {
+            if let Some(mut t) = types.remove(&name) {
+                if t.symmetric {
+                    t.inverse = Some(t.name.clone());
+                }
+                result.insert(name, t);
+            }
+        }
+        Ok(RelationRegistry { types: result })
+    }
+
+    /// Add labels from a file where each line consists of a relation type,
+    /// a language code and a label
+    pub fn add_labels<P : AsRef<Path>>(&mut self, path : P) -> Result<(), WordNetLoadError> {
+        let file = BufReader::new(File::open(path)?);
+        for line in file.lines() {
+            let line = line?;
+            if !line.starts_with("#") && !line.is_empty() {
+                let elems : Vec<&str> = line.split("\t").collect();
+                if elems.len() != 3 {
+                    return Err(WordNetLoadError::Schema("Bad line in relation labels"));
+                }
+                if let Some(t) = self.types.get_mut(elems[0]) {
+                    t.labels.insert(elems[1].to_string(), elems[2].to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name : &str) -> Option<&RelationType> {
+        self.types.get(name)
+    }
+}
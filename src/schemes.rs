@@ -0,0 +1,83 @@
+//! The identifier schemes (such as older versions of Princeton WordNet) that
+//! are mapped to the synsets of the wordnet through the ILI
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead,BufReader};
+use crate::wordnet::WordNetLoadError;
+
+/// The file that configures the identifier schemes
+pub const SCHEMES_FILE : &str = "data/schemes.tab";
+
+/// The schemes loaded if there is no configuration file, with their labels
+const DEFAULT_SCHEMES : [(&str, &str); 7] = [
+    ("pwn30", "WordNet 3.0"),
+    ("pwn21", "WordNet 2.1"),
+    ("pwn20", "WordNet 2.0"),
+    ("pwn171", "WordNet 1.7.1"),
+    ("pwn17", "WordNet 1.7"),
+    ("pwn16", "WordNet 1.6"),
+    ("pwn15", "WordNet 1.5")
+];
+
+/// An identifier scheme, served at `/<prefix>/<key>` and loaded from a file
+/// where each line consists of an ILI and a key
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct Scheme {
+    pub name : String,
+    pub prefix : String,
+    pub label : String,
+    #[serde(skip)]
+    pub path : String
+}
+
+/// The schemes of the older versions of Princeton WordNet, using the ILI
+/// mappings downloaded by `get-data.sh`
+pub fn default_schemes() -> Vec<Scheme> {
+    DEFAULT_SCHEMES.iter().map(|&(name, label)| Scheme {
+        name: name.to_string(),
+        prefix: name.to_string(),
+        label: label.to_string(),
+        path: format!("data/ili-map-{}.tab", name)
+    }).collect()
+}
+
+/// Read the identifier schemes, where each line consists of a name, the
+/// URL prefix the scheme is served at, the path of the mapping file and
+/// optionally a label (by default the name)
+pub fn read_schemes<P : AsRef<Path>>(path : P) -> Result<Vec<Scheme>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let mut result = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if !line.starts_with("#") && !line.is_empty() {
+            let elems : Vec<&str> = line.split("\t").collect();
+            if elems.len() != 3 && elems.len() != 4 {
+                return Err(WordNetLoadError::Schema("Bad line in schemes file"));
+            }
+            result.push(Scheme {
+                name: elems[0].to_string(),
+                prefix: elems[1].to_string(),
+                label: elems.get(3).unwrap_or(&elems[0]).to_string(),
+                path: elems[2].to_string()
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// The configured identifier schemes, or the default schemes if there is no
+/// configuration file
+pub fn configured_schemes() -> Result<Vec<Scheme>, WordNetLoadError> {
+    if Path::new(SCHEMES_FILE).exists() {
+        read_schemes(SCHEMES_FILE)
+    } else {
+        Ok(default_schemes())
+    }
+}
+
+/// The path of the mapping file of a scheme, if it is configured
+pub fn scheme_path(name : &str) -> Result<Option<String>, WordNetLoadError> {
+    Ok(configured_schemes()?.into_iter()
+        .find(|s| s.name == name)
+        .map(|s| s.path))
+}
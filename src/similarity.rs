@@ -0,0 +1,383 @@
+//! Measures of the semantic similarity of two synsets, based on the
+//! hypernym hierarchy and (optionally) the information content of synsets
+//! estimated from a corpus
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self,File};
+use std::path::{Path,PathBuf};
+use std::io::{BufRead,BufReader};
+use std::str::FromStr;
+use crate::taxonomy::{hypernym_paths, MAX_DEPTH};
+use crate::wordnet::{WNKey, WordNet, WordNetLoadError};
+use crate::wordnet_model::Synset;
+use crate::schemes::scheme_path;
+
+/// The score given by Jiang-Conrath to synsets at distance zero, as in NLTK
+const JCN_MAX : f64 = 1e300;
+
+/// A measure of similarity
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Measure {
+    Path, LeacockChodorow, WuPalmer, Subsumer, Resnik, Lin, JiangConrath
+}
+
+impl FromStr for Measure {
+    type Err = WordNetLoadError;
+    fn from_str(s : &str) -> Result<Measure, WordNetLoadError> {
+        match s {
+            "path" => Ok(Measure::Path),
+            "lch" => Ok(Measure::LeacockChodorow),
+            "wup" => Ok(Measure::WuPalmer),
+            "lcs" => Ok(Measure::Subsumer),
+            "res" => Ok(Measure::Resnik),
+            "lin" => Ok(Measure::Lin),
+            "jcn" => Ok(Measure::JiangConrath),
+            _ => Err(WordNetLoadError::Schema("Bad similarity measure"))
+        }
+    }
+}
+
+impl fmt::Display for Measure {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Measure::Path => "path",
+            Measure::LeacockChodorow => "lch",
+            Measure::WuPalmer => "wup",
+            Measure::Subsumer => "lcs",
+            Measure::Resnik => "res",
+            Measure::Lin => "lin",
+            Measure::JiangConrath => "jcn"
+        })
+    }
+}
+
+impl Measure {
+    /// Whether the measure needs an information content file
+    pub fn needs_ic(&self) -> bool {
+        matches!(*self, Measure::Resnik | Measure::Lin | Measure::JiangConrath)
+    }
+}
+
+/// The similarity of two synsets. The score is missing if the synsets have
+/// no common subsumer (or no information content for an IC measure) and
+/// for the `lcs` measure, which only reports the subsumer
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct Similarity {
+    pub measure : String,
+    pub synset1 : WNKey,
+    pub synset2 : WNKey,
+    pub score : Option<f64>,
+    pub subsumer : Option<WNKey>
+}
+
+/// The information content files, as `data/ic-<name>.dat`, by name
+pub fn information_content_files() -> Result<Vec<(String, PathBuf)>, WordNetLoadError> {
+    let mut result = Vec::new();
+    if Path::new("data").is_dir() {
+        for entry in fs::read_dir("data")? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|f| f.to_str())
+                .and_then(|f| f.strip_prefix("ic-"))
+                .and_then(|f| f.strip_suffix(".dat"))
+                .map(|f| f.to_string());
+            if let Some(name) = name {
+                result.push((name, path));
+            }
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Information content values read from a file in the format used by NLTK
+/// (e.g., `ic-brown.dat`), where each line gives a Princeton WordNet 3.0
+/// offset and part of speech (e.g., `1740n`), a frequency count and
+/// optionally `ROOT` if the synset is the root of a hierarchy. The counts
+/// are also indexed by the ILI, using the mapping of the `pwn30` scheme, for
+/// wordnets that do not load the WordNet 3.0 keys. The values for each
+/// synset are stored in the database when the wordnet is loaded
+pub struct InformationContent {
+    counts : HashMap<WNKey, f64>,
+    by_ili : HashMap<String, f64>,
+    roots : HashMap<char, f64>
+}
+
+impl InformationContent {
+    pub fn load<P : AsRef<Path>>(path : P) -> Result<InformationContent, WordNetLoadError> {
+        let file = BufReader::new(File::open(path)?);
+        let mut counts = HashMap::new();
+        let mut roots = HashMap::new();
+        // The first line is the WordNet version hash
+        for line in file.lines().skip(1) {
+            let line = line?;
+            let mut elems = line.split_whitespace();
+            if let (Some(key), Some(count)) = (elems.next(), elems.next()) {
+                let pos = match key.chars().last() {
+                    Some(c) if c.is_ascii_alphabetic() => c,
+                    _ => return Err(WordNetLoadError::Schema("Bad information content key"))
+                };
+                let offset = u32::from_str(&key[..key.len() - 1])?;
+                let count = f64::from_str(count)
+                    .map_err(|_| WordNetLoadError::Schema("Bad information content value"))?;
+                counts.insert(format!("{:08}-{}", offset, pos), count);
+                if elems.next() == Some("ROOT") {
+                    *roots.entry(pos).or_insert(0.0) += count;
+                }
+            }
+        }
+        let mut by_ili = HashMap::new();
+        if let Some(map) = scheme_path("pwn30")? {
+            if Path::new(&map).exists() {
+                for line in BufReader::new(File::open(map)?).lines() {
+                    let line = line?;
+                    let mut elems = line.split('\t');
+                    if let (Some(ili), Some(key)) = (elems.next(), elems.next()) {
+                        if let Some(count) = counts.get(key) {
+                            by_ili.insert(ili.to_string(), *count);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(InformationContent { counts, by_ili, roots })
+    }
+
+    /// The information content of a synset, found by its Princeton WordNet
+    /// 3.0 key, its ILI or else the WordNet 3.0 offset in its identifier
+    /// (e.g., `ewn-00001740-n`)
+    pub fn get(&self, synset : &Synset) -> Option<f64> {
+        let pos = synset.pos.to_string().chars().next()?;
+        let root = *self.roots.get(&pos)?;
+        let offset = synset.id.find('-').map(|i| &synset.id[i+1..]);
+        let count = synset.old_keys.get("pwn30").into_iter().flatten()
+            .filter_map(|k| self.counts.get(k))
+            .chain(self.by_ili.get(&synset.ili))
+            .chain(offset.and_then(|k| self.counts.get(k)))
+            .next()?;
+        if *count == 0.0 {
+            Some(f64::INFINITY)
+        } else {
+            Some(-(count / root).ln())
+        }
+    }
+}
+
+/// A synset with the distance to each of its hypernyms and the depth of
+/// each hypernym (the longest path from it to a root)
+struct Hypernyms {
+    synset : Synset,
+    distance : HashMap<WNKey, usize>,
+    depth : HashMap<WNKey, usize>
+}
+
+fn hypernyms(wordnet : &WordNet, synset : Synset) -> Result<Hypernyms, WordNetLoadError> {
+    let id = synset.id.clone();
+    let mut distance = HashMap::new();
+    let mut depth = HashMap::new();
+    for path in hypernym_paths(wordnet, &id, MAX_DEPTH * 10)? {
+        let len = path.len();
+        for (i, node) in path.into_iter().enumerate() {
+            let d = distance.entry(node.id.clone()).or_insert(i);
+            *d = (*d).min(i);
+            let d = depth.entry(node.id).or_insert(0);
+            *d = (*d).max(len - 1 - i);
+        }
+    }
+    Ok(Hypernyms { synset, distance, depth })
+}
+
+/// The subsumers shared by two synsets, ordered by id so that ties are
+/// broken consistently
+fn common(h1 : &Hypernyms, h2 : &Hypernyms) -> Vec<WNKey> {
+    let mut keys : Vec<WNKey> = h1.distance.keys()
+        .filter(|k| h2.distance.contains_key(*k))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Compute the similarity of two synsets. The name of the information
+/// content must be given for `res`, `lin` and `jcn`. Returns `None` if
+/// either synset is not in the database
+pub fn similarity(wordnet : &WordNet, measure : Measure, id1 : &WNKey, id2 : &WNKey,
+                  ic : Option<&str>)
+        -> Result<Option<Similarity>, WordNetLoadError> {
+    match (wordnet.get_synset(id1)?, wordnet.get_synset(id2)?) {
+        (Some(s1), Some(s2)) => Ok(Some(score(wordnet, measure,
+            &hypernyms(wordnet, s1)?, &hypernyms(wordnet, s2)?, ic)?)),
+        _ => Ok(None)
+    }
+}
+
+/// The path similarity of synsets at a distance
+fn path_score(distance : usize) -> f64 {
+    1.0 / (distance as f64 + 1.0)
+}
+
+/// The Leacock-Chodorow similarity of synsets at a distance in a hierarchy
+/// of a maximum depth
+fn lch_score(distance : usize, max_depth : u32) -> f64 {
+    let max_depth = (max_depth as f64).max(1.0);
+    -((distance as f64 + 1.0) / (2.0 * max_depth)).ln()
+}
+
+/// The Wu-Palmer similarity of synsets at distances from a subsumer of a
+/// depth
+fn wup_score(depth : usize, distance1 : usize, distance2 : usize) -> f64 {
+    let depth = depth as f64 + 1.0;
+    let len1 = distance1 as f64 + depth;
+    let len2 = distance2 as f64 + depth;
+    2.0 * depth / (len1 + len2)
+}
+
+/// The similarity of synsets by an information content measure, given the
+/// information content of the subsumer and the synsets
+fn ic_score(measure : Measure, ic_lcs : f64, ic1 : f64, ic2 : f64) -> Option<f64> {
+    Some(match measure {
+        Measure::Resnik => ic_lcs,
+        Measure::Lin => 2.0 * ic_lcs / (ic1 + ic2),
+        _ => {
+            if ic1 == 0.0 || ic2 == 0.0 {
+                0.0
+            } else {
+                let d = ic1 + ic2 - 2.0 * ic_lcs;
+                if d == 0.0 { JCN_MAX } else { 1.0 / d }
+            }
+        }
+    }).filter(|s| s.is_finite())
+}
+
+fn score(wordnet : &WordNet, measure : Measure, h1 : &Hypernyms, h2 : &Hypernyms,
+         ic : Option<&str>) -> Result<Similarity, WordNetLoadError> {
+    let common = common(h1, h2);
+    let mut result = Similarity {
+        measure: measure.to_string(),
+        synset1: h1.synset.id.clone(),
+        synset2: h2.synset.id.clone(),
+        score: None,
+        subsumer: None
+    };
+    if common.is_empty() {
+        return Ok(result);
+    }
+    let distance = common.iter()
+        .map(|k| h1.distance[k] + h2.distance[k])
+        .min()
+        .expect("Common subsumers cannot be empty");
+    // The lowest common subsumer is the deepest, breaking ties by distance
+    let lcs = common.iter()
+        .max_by_key(|k| (h1.depth[*k], std::cmp::Reverse(h1.distance[*k] + h2.distance[*k])))
+        .expect("Common subsumers cannot be empty")
+        .clone();
+    match measure {
+        Measure::Path => {
+            result.score = Some(path_score(distance));
+            result.subsumer = Some(lcs);
+        },
+        Measure::LeacockChodorow => {
+            let pos1 = h1.synset.pos.to_string();
+            if pos1 == h2.synset.pos.to_string() {
+                if let Some(max_depth) = wordnet.taxonomy_depth(&pos1)? {
+                    result.score = Some(lch_score(distance, max_depth));
+                }
+            }
+            result.subsumer = Some(lcs);
+        },
+        Measure::WuPalmer => {
+            result.score = Some(wup_score(h1.depth[&lcs], h1.distance[&lcs], h2.distance[&lcs]));
+            result.subsumer = Some(lcs);
+        },
+        Measure::Subsumer => {
+            result.subsumer = Some(lcs);
+        },
+        Measure::Resnik | Measure::Lin | Measure::JiangConrath => {
+            let ic = ic.ok_or(WordNetLoadError::Schema("No information content for measure"))?;
+            let mut keys = common.clone();
+            keys.push(h1.synset.id.clone());
+            keys.push(h2.synset.id.clone());
+            let values = wordnet.information_content(ic, &keys)?;
+            // The subsumer used is the one with the most information content
+            let mut best : Option<(WNKey, f64)> = None;
+            for k in common.iter() {
+                if let Some(&value) = values.get(k) {
+                    if best.as_ref().map(|b| value > b.1).unwrap_or(true) {
+                        best = Some((k.clone(), value));
+                    }
+                }
+            }
+            if let (Some((subsumer, ic_lcs)), Some(&ic1), Some(&ic2)) =
+                    (best, values.get(&h1.synset.id), values.get(&h2.synset.id)) {
+                result.score = ic_score(measure, ic_lcs, ic1, ic2);
+                result.subsumer = Some(subsumer);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The similarity of two lemmas, being the best score over all pairs of
+/// their senses in a language. Pairs without a score (or without a subsumer
+/// for `lcs`) are skipped, so `None` is returned if no pair has one
+pub fn lemma_similarity(wordnet : &WordNet, measure : Measure, lemma1 : &str,
+                        lemma2 : &str, lang : &str, ic : Option<&str>)
+        -> Result<Option<Similarity>, WordNetLoadError> {
+    let senses1 = wordnet.get_by_lemma(lemma1, lang)?.into_iter()
+        .map(|s| hypernyms(wordnet, s))
+        .collect::<Result<Vec<Hypernyms>, WordNetLoadError>>()?;
+    let senses2 = wordnet.get_by_lemma(lemma2, lang)?.into_iter()
+        .map(|s| hypernyms(wordnet, s))
+        .collect::<Result<Vec<Hypernyms>, WordNetLoadError>>()?;
+    let mut best : Option<Similarity> = None;
+    for h1 in senses1.iter() {
+        for h2 in senses2.iter() {
+            let sim = score(wordnet, measure, h1, h2, ic)?;
+            if sim.score.is_none() && (measure != Measure::Subsumer || sim.subsumer.is_none()) {
+                continue;
+            }
+            if best.as_ref().map(|b| sim.score > b.score).unwrap_or(true) {
+                best = Some(sim);
+            }
+        }
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a : f64, b : f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_path_score() {
+        assert!(close(path_score(0), 1.0));
+        assert!(close(path_score(1), 0.5));
+        assert!(close(path_score(4), 0.2));
+    }
+
+    #[test]
+    fn test_lch_score() {
+        // dog and cat are four hypernym links apart in a hierarchy of depth 19
+        assert!(close(lch_score(4, 19), 2.0281482472922856));
+        assert!(close(lch_score(0, 0), 2.0f64.ln()));
+    }
+
+    #[test]
+    fn test_wup_score() {
+        assert!(close(wup_score(12, 2, 2), 26.0 / 30.0));
+        assert!(close(wup_score(3, 0, 0), 1.0));
+    }
+
+    #[test]
+    fn test_ic_score() {
+        assert_eq!(ic_score(Measure::Resnik, 7.0, 9.0, 10.0), Some(7.0));
+        assert!(close(ic_score(Measure::Lin, 7.0, 9.0, 10.0).unwrap(), 14.0 / 19.0));
+        assert!(close(ic_score(Measure::JiangConrath, 7.0, 9.0, 10.0).unwrap(), 0.2));
+        assert_eq!(ic_score(Measure::JiangConrath, 7.0, 7.0, 7.0), Some(JCN_MAX));
+        assert_eq!(ic_score(Measure::JiangConrath, 0.0, 0.0, 7.0), Some(0.0));
+        assert_eq!(ic_score(Measure::Resnik, f64::INFINITY, 9.0, 10.0), None);
+    }
+}
@@ -0,0 +1,483 @@
+//! Traversal of the taxonomy (and other relations) of the wordnet, so that
+//! paths and subtrees can be returned in a single request
+use std::collections::{HashMap,HashSet};
+use crate::wordnet::{WNKey, WordNet, WordNetLoadError};
+use crate::wordnet_model::Synset;
+
+/// The relations that link a synset to its more general synsets
+pub const HYPERNYMS : [&str; 2] = ["hypernym", "instance_hypernym"];
+/// The relations that link a synset to its more specific synsets
+pub const HYPONYMS : [&str; 2] = ["hyponym", "instance_hyponym"];
+
+/// The deepest that any traversal will go
+pub const MAX_DEPTH : usize = 100;
+
+/// The most synsets that a path search will visit before giving up
+pub const MAX_VISITED : usize = 100000;
+
+/// A synset in a taxonomy, with its lemmas and definition so that a client
+/// can display it without looking it up
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct TaxonomyNode {
+    pub id : WNKey,
+    pub lemmas : Vec<String>,
+    pub definition : String,
+    pub depth : usize,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children : Vec<TaxonomyNode>
+}
+
+impl TaxonomyNode {
+    fn new(synset : &Synset, depth : usize) -> TaxonomyNode {
+        TaxonomyNode {
+            id: synset.id.clone(),
+            lemmas: synset.lemmas.iter().map(|s| s.lemma.clone()).collect(),
+            definition: synset.definition.clone(),
+            depth,
+            children: Vec::new()
+        }
+    }
+}
+
+/// The targets of the relations of a synset of the given types
+pub fn targets(synset : &Synset, rel_types : &[&str]) -> Vec<WNKey> {
+    let mut result : Vec<WNKey> = Vec::new();
+    for r in synset.relations.iter() {
+        if rel_types.contains(&r.rel_type.as_str()) && !result.contains(&r.target) {
+            result.push(r.target.clone());
+        }
+    }
+    result
+}
+
+fn fetch(wordnet : &WordNet, keys : &[WNKey], cache : &mut HashMap<WNKey, Synset>)
+        -> Result<(), WordNetLoadError> {
+    let missing : Vec<WNKey> = keys.iter()
+        .filter(|k| !cache.contains_key(*k))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        for synset in wordnet.get_synsets_by_ids(&missing)? {
+            cache.insert(synset.id.clone(), synset);
+        }
+    }
+    Ok(())
+}
+
+/// All paths from a synset up to the roots of the hypernym hierarchy. Each
+/// path starts with the synset itself. At most `limit` paths are returned
+pub fn hypernym_paths(wordnet : &WordNet, id : &WNKey, limit : usize)
+        -> Result<Vec<Vec<TaxonomyNode>>, WordNetLoadError> {
+    let mut cache = HashMap::new();
+    fetch(wordnet, std::slice::from_ref(id), &mut cache)?;
+    if !cache.contains_key(id) {
+        return Ok(Vec::new());
+    }
+    let mut complete : Vec<Vec<WNKey>> = Vec::new();
+    let mut partial : Vec<Vec<WNKey>> = vec![vec![id.clone()]];
+    while !partial.is_empty() && complete.len() < limit {
+        let tops : Vec<WNKey> = partial.iter()
+            .filter_map(|p| p.last().cloned())
+            .flat_map(|k| cache.get(&k).map(|s| targets(s, &HYPERNYMS)).unwrap_or_default())
+            .collect();
+        fetch(wordnet, &tops, &mut cache)?;
+        let mut next = Vec::new();
+        for path in partial {
+            let top = cache.get(path.last().expect("Path cannot be empty"))
+                .map(|s| targets(s, &HYPERNYMS))
+                .unwrap_or_default();
+            // Ignore hypernyms that are already on the path (cycles) or are
+            // not in the database
+            let parents : Vec<WNKey> = top.into_iter()
+                .filter(|k| !path.contains(k) && cache.contains_key(k))
+                .collect();
+            if parents.is_empty() || path.len() >= MAX_DEPTH {
+                complete.push(path);
+            } else {
+                for parent in parents {
+                    let mut p = path.clone();
+                    p.push(parent);
+                    next.push(p);
+                }
+            }
+        }
+        partial = next;
+    }
+    complete.truncate(limit);
+    Ok(complete.into_iter().map(|path| {
+        path.iter().enumerate()
+            .map(|(depth, k)| TaxonomyNode::new(&cache[k], depth))
+            .collect()
+    }).collect())
+}
+
+/// The tree of hyponyms below a synset to a given depth. At most `limit`
+/// synsets are included in the tree
+pub fn hyponym_tree(wordnet : &WordNet, id : &WNKey, depth : usize, limit : usize)
+        -> Result<Option<TaxonomyNode>, WordNetLoadError> {
+    let mut cache = HashMap::new();
+    fetch(wordnet, std::slice::from_ref(id), &mut cache)?;
+    let root = match cache.get(id) {
+        Some(s) => TaxonomyNode::new(s, 0),
+        None => return Ok(None)
+    };
+    // Nodes are stored in an arena with the ancestors of each node, to
+    // avoid following cycles, and then assembled into a tree
+    let mut arena : Vec<(TaxonomyNode, Vec<usize>, HashSet<WNKey>)> =
+        vec![(root, Vec::new(), vec![id.clone()].into_iter().collect())];
+    let mut level = vec![0];
+    for d in 1..=depth.min(MAX_DEPTH) {
+        let keys : Vec<WNKey> = level.iter()
+            .flat_map(|i| targets(&cache[&arena[*i].0.id], &HYPONYMS))
+            .collect();
+        fetch(wordnet, &keys, &mut cache)?;
+        let mut next = Vec::new();
+        'level: for i in level {
+            for k in targets(&cache[&arena[i].0.id], &HYPONYMS) {
+                if arena.len() >= limit {
+                    break 'level;
+                }
+                if arena[i].2.contains(&k) {
+                    continue;
+                }
+                if let Some(s) = cache.get(&k) {
+                    let mut ancestors = arena[i].2.clone();
+                    ancestors.insert(k.clone());
+                    let j = arena.len();
+                    arena.push((TaxonomyNode::new(s, d), Vec::new(), ancestors));
+                    arena[i].1.push(j);
+                    next.push(j);
+                }
+            }
+        }
+        level = next;
+        if level.is_empty() {
+            break;
+        }
+    }
+    fn assemble(arena : &[(TaxonomyNode, Vec<usize>, HashSet<WNKey>)], i : usize) -> TaxonomyNode {
+        let mut node = arena[i].0.clone();
+        node.children = arena[i].1.iter().map(|j| assemble(arena, *j)).collect();
+        node
+    }
+    Ok(Some(assemble(&arena, 0)))
+}
+
+/// The transitive closure of a relation from a synset, in breadth-first
+/// order. The synset itself is not included. At most `limit` synsets are
+/// returned
+pub fn closure(wordnet : &WordNet, id : &WNKey, rel_type : &str, limit : usize)
+        -> Result<Vec<TaxonomyNode>, WordNetLoadError> {
+    let mut cache = HashMap::new();
+    fetch(wordnet, std::slice::from_ref(id), &mut cache)?;
+    let mut visited : HashSet<WNKey> = HashSet::new();
+    visited.insert(id.clone());
+    let mut result = Vec::new();
+    let mut level = vec![id.clone()];
+    let mut depth = 0;
+    while !level.is_empty() && depth < MAX_DEPTH && result.len() < limit {
+        depth += 1;
+        let keys : Vec<WNKey> = level.iter()
+            .filter_map(|k| cache.get(k))
+            .flat_map(|s| targets(s, &[rel_type]))
+            .filter(|k| !visited.contains(k))
+            .collect();
+        fetch(wordnet, &keys, &mut cache)?;
+        let mut next = Vec::new();
+        for k in keys {
+            if result.len() >= limit {
+                break;
+            }
+            if visited.insert(k.clone()) {
+                if let Some(s) = cache.get(&k) {
+                    result.push(TaxonomyNode::new(s, depth));
+                    next.push(k);
+                }
+            }
+        }
+        level = next;
+    }
+    Ok(result)
+}
+
+/// A relation on a path between two synsets, in the direction that it is
+/// stored in the wordnet (which may be against the direction of the path)
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct PathEdge {
+    pub source : WNKey,
+    pub target : WNKey,
+    pub rel_type : String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub src_word : Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub trg_word : Option<String>
+}
+
+/// A chain of synsets linking two synsets, where `relations[i]` links
+/// `synsets[i]` and `synsets[i+1]`
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct SynsetPath {
+    pub synsets : Vec<TaxonomyNode>,
+    pub relations : Vec<PathEdge>
+}
+
+/// The edges from a synset, optionally restricted to some relation types
+fn edges(synset : &Synset, rel_types : Option<&[&str]>) -> Vec<PathEdge> {
+    synset.relations.iter()
+        .filter(|r| rel_types.map(|t| t.contains(&r.rel_type.as_str())).unwrap_or(true))
+        .map(|r| PathEdge {
+            source: synset.id.clone(),
+            target: r.target.clone(),
+            rel_type: r.rel_type.clone(),
+            src_word: r.src_word.clone(),
+            trg_word: r.trg_word.clone()
+        })
+        .collect()
+}
+
+/// The synsets reached from one end of a path search, with the number of
+/// hops to each and the synset and edge it was reached from
+type Visited = HashMap<WNKey, (usize, Option<(WNKey, PathEdge)>)>;
+
+/// A step of a path search, being a synset, a neighbour and the edge between
+/// them, which may be outgoing or incoming
+type Step = (WNKey, WNKey, PathEdge);
+
+/// The synsets on a path and the edges linking them
+type Path = (Vec<WNKey>, Vec<PathEdge>);
+
+/// The shortest chain of relations between two synsets, by a bidirectional
+/// breadth-first search. Both synset and sense relations are followed, in
+/// either direction (using the incoming relation index), and may be
+/// restricted to some relation types. Returns
+/// `None` if there is no path of at most `max_hops` relations
+pub fn shortest_path(wordnet : &WordNet, id1 : &WNKey, id2 : &WNKey,
+                     rel_types : Option<&[&str]>, max_hops : usize)
+        -> Result<Option<SynsetPath>, WordNetLoadError> {
+    let mut cache = HashMap::new();
+    fetch(wordnet, &[id1.clone(), id2.clone()], &mut cache)?;
+    if !cache.contains_key(id1) || !cache.contains_key(id2) {
+        return Ok(None);
+    }
+    let path = search(id1, id2, max_hops, |level| {
+        let mut steps : Vec<Step> = level.iter()
+            .filter_map(|k| cache.get(k))
+            .flat_map(|s| edges(s, rel_types))
+            .map(|e| (e.source.clone(), e.target.clone(), e))
+            .collect();
+        steps.extend(wordnet.get_incoming(level, None)?.into_iter()
+            .filter(|e| rel_types.map(|t| t.contains(&e.rel_type.as_str())).unwrap_or(true))
+            .map(|e| (e.target.clone(), e.source.clone(), e)));
+        let neighbours : Vec<WNKey> = steps.iter().map(|s| s.1.clone()).collect();
+        fetch(wordnet, &neighbours, &mut cache)?;
+        // Skip synsets that are not in the database
+        Ok(steps.into_iter().filter(|s| cache.contains_key(&s.1)).collect())
+    })?;
+    Ok(path.map(|(keys, relations)| SynsetPath {
+        synsets: keys.iter().enumerate()
+            .map(|(i, k)| TaxonomyNode::new(&cache[k], i))
+            .collect(),
+        relations
+    }))
+}
+
+/// A bidirectional breadth-first search between two synsets, where `expand`
+/// gives the steps from a frontier. Returns the synsets on the path and the
+/// edges linking them
+fn search<F>(id1 : &WNKey, id2 : &WNKey, max_hops : usize, mut expand : F)
+        -> Result<Option<Path>, WordNetLoadError>
+        where F : FnMut(&[WNKey]) -> Result<Vec<Step>, WordNetLoadError> {
+    let mut forward : Visited = HashMap::new();
+    let mut backward : Visited = HashMap::new();
+    forward.insert(id1.clone(), (0, None));
+    backward.insert(id2.clone(), (0, None));
+    let mut forward_level = vec![id1.clone()];
+    let mut backward_level = vec![id2.clone()];
+    let mut meeting = if id1 == id2 { Some(id1.clone()) } else { None };
+    let mut hops = 0;
+    while meeting.is_none() && hops < max_hops
+            && !forward_level.is_empty() && !backward_level.is_empty()
+            && forward.len() + backward.len() < MAX_VISITED {
+        hops += 1;
+        // Expand the smaller frontier
+        let (level, visited, other) = if forward_level.len() <= backward_level.len() {
+            (&mut forward_level, &mut forward, &backward)
+        } else {
+            (&mut backward_level, &mut backward, &forward)
+        };
+        let mut next = Vec::new();
+        let mut best : Option<(usize, WNKey)> = None;
+        for (k, t, edge) in expand(level)? {
+            let depth = visited[&k].0 + 1;
+            // Skip synsets already reached
+            if visited.contains_key(&t) {
+                continue;
+            }
+            if let Some(&(d, _)) = other.get(&t) {
+                if best.as_ref().map(|b| d < b.0).unwrap_or(true) {
+                    best = Some((d, t.clone()));
+                }
+            }
+            visited.insert(t.clone(), (depth, Some((k, edge))));
+            next.push(t);
+        }
+        *level = next;
+        meeting = best.map(|b| b.1);
+    }
+    let meeting = match meeting {
+        Some(m) => m,
+        None => return Ok(None)
+    };
+    // Follow the parents back to each end
+    let mut keys = vec![meeting.clone()];
+    let mut relations = Vec::new();
+    let mut k = meeting.clone();
+    while let Some((parent, edge)) = forward[&k].1.clone() {
+        keys.insert(0, parent.clone());
+        relations.insert(0, edge);
+        k = parent;
+    }
+    let mut k = meeting;
+    while let Some((parent, edge)) = backward[&k].1.clone() {
+        keys.push(parent.clone());
+        relations.push(edge);
+        k = parent;
+    }
+    Ok(Some((keys, relations)))
+}
+
+/// A relation pointing to a synset, with the synset it comes from
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct IncomingRelation {
+    pub relation : PathEdge,
+    pub synset : TaxonomyNode
+}
+
+/// The relations that point to a synset, optionally only of one type
+pub fn incoming(wordnet : &WordNet, id : &WNKey, rel_type : Option<&str>)
+        -> Result<Vec<IncomingRelation>, WordNetLoadError> {
+    let relations = wordnet.get_incoming(std::slice::from_ref(id), rel_type)?;
+    let mut cache = HashMap::new();
+    let sources : Vec<WNKey> = relations.iter().map(|r| r.source.clone()).collect();
+    fetch(wordnet, &sources, &mut cache)?;
+    Ok(relations.into_iter()
+        .filter_map(|r| {
+            cache.get(&r.source).map(|s| IncomingRelation {
+                synset: TaxonomyNode::new(s, 1),
+                relation: r
+            })
+        })
+        .collect())
+}
+
+/// The maximum depth of the hypernym hierarchy for each part of speech, that
+/// is the longest path (in relations) from any synset up to a root
+pub fn max_depths(synsets : &HashMap<WNKey, Synset>) -> HashMap<String, usize> {
+    fn depth(synsets : &HashMap<WNKey, Synset>, key : &WNKey,
+             memo : &mut HashMap<WNKey, usize>, visiting : &mut HashSet<WNKey>) -> usize {
+        if let Some(d) = memo.get(key) {
+            return *d;
+        }
+        visiting.insert(key.clone());
+        let mut d = 0;
+        if let Some(synset) = synsets.get(key) {
+            for k in targets(synset, &HYPERNYMS) {
+                // Skip cycles and hypernyms that are not in the wordnet
+                if !visiting.contains(&k) && synsets.contains_key(&k) {
+                    d = d.max(depth(synsets, &k, memo, visiting) + 1);
+                }
+            }
+        }
+        visiting.remove(key);
+        memo.insert(key.clone(), d);
+        d
+    }
+    let mut memo = HashMap::new();
+    let mut visiting = HashSet::new();
+    let mut result : HashMap<String, usize> = HashMap::new();
+    for (key, synset) in synsets.iter() {
+        let d = depth(synsets, key, &mut memo, &mut visiting);
+        let max = result.entry(synset.pos.to_string()).or_insert(0);
+        *max = (*max).max(d);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(source : &str, target : &str) -> PathEdge {
+        PathEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            rel_type: "hypernym".to_string(),
+            src_word: None,
+            trg_word: None
+        }
+    }
+
+    /// Search a small graph where a and d are hyponyms (through b) of c
+    fn search_graph(id1 : &str, id2 : &str, max_hops : usize)
+            -> Option<Path> {
+        let graph = [edge("a", "b"), edge("b", "c"), edge("d", "c"), edge("e", "f")];
+        search(&id1.to_string(), &id2.to_string(), max_hops, |level| {
+            let mut steps = Vec::new();
+            for e in graph.iter() {
+                if level.contains(&e.source) {
+                    steps.push((e.source.clone(), e.target.clone(), e.clone()));
+                }
+                if level.contains(&e.target) {
+                    steps.push((e.target.clone(), e.source.clone(), e.clone()));
+                }
+            }
+            Ok(steps)
+        }).unwrap()
+    }
+
+    fn synset(id : &str, pos : &str, hypernyms : &[&str]) -> (WNKey, Synset) {
+        let relations : Vec<serde_json::Value> = hypernyms.iter().map(|h| serde_json::json!({
+            "src_word": null, "trg_word": null, "rel_type": "hypernym", "target": h
+        })).collect();
+        let synset = serde_json::from_value(serde_json::json!({
+            "definition": "", "examples": [], "lemmas": [], "id": id, "ili": "",
+            "pos": pos, "subject": "", "relations": relations, "old_keys": {},
+            "gloss": null, "foreign": {}, "links": []
+        })).unwrap();
+        (id.to_string(), synset)
+    }
+
+    #[test]
+    fn test_max_depths() {
+        let synsets : HashMap<WNKey, Synset> = vec![
+            synset("entity", "n", &[]),
+            synset("animal", "n", &["entity"]),
+            synset("dog", "n", &["animal", "pet"]),
+            synset("pet", "n", &["animal"]),
+            synset("puppy", "n", &["dog"]),
+            synset("run", "v", &[]),
+            synset("sprint", "v", &["run", "missing"])
+        ].into_iter().collect();
+        let depths = max_depths(&synsets);
+        assert_eq!(depths.get("n"), Some(&4));
+        assert_eq!(depths.get("v"), Some(&1));
+        assert_eq!(depths.get("a"), None);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let (keys, relations) = search_graph("a", "d", 10).unwrap();
+        assert_eq!(keys, vec!["a", "b", "c", "d"]);
+        assert_eq!(relations.iter().map(|e| (e.source.as_str(), e.target.as_str())).collect::<Vec<_>>(),
+                   vec![("a", "b"), ("b", "c"), ("d", "c")]);
+        let (keys, relations) = search_graph("c", "a", 10).unwrap();
+        assert_eq!(keys, vec!["c", "b", "a"]);
+        assert_eq!(relations.len(), 2);
+        let (keys, relations) = search_graph("a", "a", 10).unwrap();
+        assert_eq!(keys, vec!["a"]);
+        assert!(relations.is_empty());
+        assert!(search_graph("a", "d", 2).is_none());
+        assert!(search_graph("a", "d", 3).is_some());
+        assert!(search_graph("a", "e", 10).is_none());
+    }
+}
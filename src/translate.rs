@@ -0,0 +1,81 @@
+//! Translation of words between languages through the synsets they share,
+//! either directly or through the interlingual index
+use std::collections::HashSet;
+use crate::omwn::language_code;
+use crate::wordnet::{WNKey, WordNet, WordNetLoadError};
+use crate::wordnet_model::Synset;
+
+/// The translations of a word for one of its senses
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct Translation {
+    pub synset : WNKey,
+    pub ili : String,
+    pub pos : String,
+    pub definition : String,
+    /// The definition in the target language, if there is one
+    pub target_definition : Option<String>,
+    pub lemmas : Vec<String>
+}
+
+/// The lemmas and definition of a synset in a language, from its senses and
+/// the lemmas loaded from the Open Multilingual Wordnet. The definition of
+/// the synset is only used if its lexicon is in the language
+fn target_lemmas(synset : &Synset, lang : &str, lemmas : &mut Vec<String>,
+                 definition : &mut Option<String>) {
+    for sense in synset.lemmas.iter() {
+        if sense.language == lang && !lemmas.contains(&sense.lemma) {
+            lemmas.push(sense.lemma.clone());
+        }
+    }
+    if synset.language == lang && definition.is_none() && !synset.definition.is_empty() {
+        *definition = Some(synset.definition.clone());
+    }
+    for (omw_lang, values) in synset.foreign.iter() {
+        if language_code(omw_lang) == lang {
+            for v in values.iter() {
+                if !lemmas.contains(v) {
+                    lemmas.push(v.clone());
+                }
+            }
+        }
+    }
+    for (omw_lang, values) in synset.foreign_definitions.iter() {
+        if language_code(omw_lang) == lang && definition.is_none() {
+            *definition = values.first().cloned();
+        }
+    }
+}
+
+/// Translate a word by finding all its senses in the source language and
+/// the lemmas of the target language for each of these senses
+pub fn translate(wordnet : &WordNet, word : &str, from : &str, to : &str)
+        -> Result<Vec<Translation>, WordNetLoadError> {
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+    for synset in wordnet.get_by_lemma(word, from)? {
+        if !seen.insert(synset.id.clone()) {
+            continue;
+        }
+        let mut lemmas = Vec::new();
+        let mut target_definition = None;
+        target_lemmas(&synset, to, &mut lemmas, &mut target_definition);
+        // Other lexicons link their synsets through the ILI, except for new
+        // concepts (`in`) that are not yet in the index
+        if !synset.ili.is_empty() && synset.ili != "in" {
+            for other in wordnet.get_all_by_ili(&synset.ili)? {
+                if other.id != synset.id {
+                    target_lemmas(&other, to, &mut lemmas, &mut target_definition);
+                }
+            }
+        }
+        result.push(Translation {
+            synset: synset.id.clone(),
+            ili: synset.ili.clone(),
+            pos: synset.pos.to_string(),
+            definition: synset.definition.clone(),
+            target_definition,
+            lemmas
+        });
+    }
+    Ok(result)
+}
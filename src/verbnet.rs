@@ -0,0 +1,246 @@
+use std::path::Path;
+use crate::wordnet::{WNKey,WordNetLoadError};
+use crate::wordnet_model::Synset;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::fs::{read_dir,File};
+use std::ffi::OsStr;
+use xml::reader::{EventReader, XmlEvent};
+use xml::attribute::OwnedAttribute;
+
+/// The directory the VerbNet XML files are read from
+pub const VERBNET_DIR : &str = "data/verbnet";
+
+/// A VerbNet class or subclass. Subclasses have the roles and frames of
+/// their ancestors as well as their own
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+pub struct VerbNetClass {
+    pub id : String,
+    pub parent : Option<String>,
+    /// The top-level class, whose page on the VerbNet site shows this class
+    #[serde(default)]
+    pub root : String,
+    pub members : Vec<VerbNetMember>,
+    pub roles : Vec<ThematicRole>,
+    pub subclasses : Vec<String>,
+    pub frames : Vec<VerbNetFrame>
+}
+
+/// A verb of a class with the sense keys of its WordNet senses
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct VerbNetMember {
+    pub name : String,
+    pub sense_keys : Vec<String>
+}
+
+/// A thematic role with its selectional restrictions (e.g., `+animate`)
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+pub struct ThematicRole {
+    pub role_type : String,
+    pub restrictions : Vec<String>
+}
+
+/// A frame, giving its syntax (e.g., `NP.Agent V NP.Theme`) and the
+/// predicates of its semantics (e.g., `motion(during(E), Theme)`)
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+pub struct VerbNetFrame {
+    pub description : String,
+    pub examples : Vec<String>,
+    pub syntax : Vec<String>,
+    pub semantics : Vec<String>
+}
+
+/// A class with the synsets of its members
+#[derive(Clone,Debug,Serialize)]
+pub struct VerbNetClassSynsets {
+    #[serde(flatten)]
+    pub class : VerbNetClass,
+    pub synsets : Vec<Synset>
+}
+
+fn attr<'a>(attributes : &'a [OwnedAttribute], name : &str) -> Option<&'a str> {
+    attributes.iter().find(|a| a.name.local_name == name)
+        .map(|a| a.value.as_str())
+}
+
+/// Read the classes and subclasses of a VerbNet file
+fn load_classes<P : AsRef<Path>>(path : P) -> Result<Vec<VerbNetClass>, WordNetLoadError> {
+    let file = BufReader::new(File::open(path)?);
+    let parse = EventReader::new(file);
+
+    let mut classes = Vec::new();
+    let mut stack : Vec<VerbNetClass> = Vec::new();
+    let mut role : Option<ThematicRole> = None;
+    let mut frame : Option<VerbNetFrame> = None;
+    let mut predicate : Option<(String, Vec<String>)> = None;
+    let mut in_example = false;
+    let mut syntax_depth : Option<u32> = None;
+
+    for e in parse {
+        match e {
+            Ok(XmlEvent::StartElement{ name, attributes, .. }) => {
+                let name = name.local_name;
+                if let Some(depth) = syntax_depth {
+                    if depth == 0 {
+                        let value = attr(&attributes, "value").unwrap_or("");
+                        let elem = match name.as_str() {
+                            "VERB" => "V".to_string(),
+                            "LEX" => value.to_string(),
+                            _ if value.is_empty() => name.clone(),
+                            _ => format!("{}.{}", name, value)
+                        };
+                        if let Some(ref mut f) = frame {
+                            f.syntax.push(elem);
+                        }
+                    }
+                    syntax_depth = Some(depth + 1);
+                } else if name == "VNCLASS" || name == "VNSUBCLASS" {
+                    stack.push(VerbNetClass {
+                        id: attr(&attributes, "ID").unwrap_or("").to_string(),
+                        parent: stack.last().map(|c| c.id.clone()),
+                        ..VerbNetClass::default()
+                    });
+                } else if name == "MEMBER" {
+                    // Uncertain mappings are marked with a `?`
+                    let sense_keys = attr(&attributes, "wn").unwrap_or("")
+                        .split_whitespace()
+                        .filter(|wn| !wn.starts_with("?"))
+                        .map(|wn| format!("{}::", wn))
+                        .collect();
+                    if let Some(class) = stack.last_mut() {
+                        class.members.push(VerbNetMember {
+                            name: attr(&attributes, "name").unwrap_or("").to_string(),
+                            sense_keys
+                        });
+                    }
+                } else if name == "THEMROLE" {
+                    role = Some(ThematicRole {
+                        role_type: attr(&attributes, "type").unwrap_or("").to_string(),
+                        restrictions: Vec::new()
+                    });
+                } else if name == "SELRESTR" {
+                    if let Some(ref mut r) = role {
+                        r.restrictions.push(format!("{}{}",
+                            attr(&attributes, "Value").unwrap_or(""),
+                            attr(&attributes, "type").unwrap_or("")));
+                    }
+                } else if name == "FRAME" {
+                    frame = Some(VerbNetFrame::default());
+                } else if name == "DESCRIPTION" {
+                    if let Some(ref mut f) = frame {
+                        f.description = attr(&attributes, "primary").unwrap_or("").to_string();
+                    }
+                } else if name == "EXAMPLE" {
+                    in_example = true;
+                } else if name == "SYNTAX" {
+                    syntax_depth = Some(0);
+                } else if name == "PRED" {
+                    let negated = attr(&attributes, "bool") == Some("!");
+                    let value = attr(&attributes, "value").unwrap_or("");
+                    predicate = Some((if negated { format!("not {}", value) } else { value.to_string() },
+                                      Vec::new()));
+                } else if name == "ARG" {
+                    if let Some((_, ref mut args)) = predicate {
+                        args.push(attr(&attributes, "value").unwrap_or("").to_string());
+                    }
+                }
+            },
+            Ok(XmlEvent::Characters(s)) => {
+                if in_example {
+                    if let Some(ref mut f) = frame {
+                        f.examples.push(s);
+                    }
+                }
+            },
+            Ok(XmlEvent::EndElement{ name }) => {
+                let name = name.local_name;
+                if name == "SYNTAX" {
+                    syntax_depth = None;
+                } else if let Some(depth) = syntax_depth {
+                    syntax_depth = Some(depth - 1);
+                } else if name == "VNCLASS" || name == "VNSUBCLASS" {
+                    if let Some(class) = stack.pop() {
+                        if let Some(parent) = stack.last_mut() {
+                            parent.subclasses.push(class.id.clone());
+                        }
+                        classes.push(class);
+                    }
+                } else if name == "THEMROLE" {
+                    if let (Some(r), Some(class)) = (role.take(), stack.last_mut()) {
+                        class.roles.push(r);
+                    }
+                } else if name == "FRAME" {
+                    if let (Some(f), Some(class)) = (frame.take(), stack.last_mut()) {
+                        class.frames.push(f);
+                    }
+                } else if name == "EXAMPLE" {
+                    in_example = false;
+                } else if name == "PRED" {
+                    if let (Some((value, args)), Some(ref mut f)) = (predicate.take(), frame.as_mut()) {
+                        f.semantics.push(format!("{}({})", value, args.join(", ")));
+                    }
+                }
+            },
+            Ok(_) => {},
+            Err(e) => return Err(WordNetLoadError::Xml(e))
+        }
+    }
+
+    Ok(classes)
+}
+
+/// Give each class its root and each subclass the roles and frames of its
+/// ancestors. A role of a subclass replaces the role of the same type of an
+/// ancestor, as it only refines the selectional restrictions
+fn inherit(classes : Vec<VerbNetClass>) -> Vec<VerbNetClass> {
+    let by_id : HashMap<String, VerbNetClass> = classes.iter()
+        .map(|c| (c.id.clone(), c.clone())).collect();
+    classes.into_iter().map(|mut class| {
+        class.root = class.id.clone();
+        let mut parent = class.parent.as_ref().and_then(|p| by_id.get(p));
+        while let Some(p) = parent {
+            let mut roles : Vec<ThematicRole> = p.roles.iter()
+                .filter(|r| !class.roles.iter().any(|r2| r2.role_type == r.role_type))
+                .cloned().collect();
+            roles.append(&mut class.roles);
+            class.roles = roles;
+            let mut frames = p.frames.clone();
+            frames.append(&mut class.frames);
+            class.frames = frames;
+            class.root = p.id.clone();
+            parent = p.parent.as_ref().and_then(|p| by_id.get(p));
+        }
+        class
+    }).collect()
+}
+
+/// Read all the classes in the VerbNet directory
+pub fn load_verbnet() -> Result<Vec<VerbNetClass>, WordNetLoadError> {
+    let paths = read_dir(VERBNET_DIR)?;
+    let mut classes = Vec::new();
+
+    for path in paths {
+        let path = path?.path();
+        if path.extension() == Some(OsStr::new("xml")) {
+            classes.extend(load_classes(path)?);
+        }
+    }
+    Ok(inherit(classes))
+}
+
+/// The synsets of the members of a class
+pub fn member_synsets<F>(class : &VerbNetClass, mut by_sense_key : F)
+    -> Result<Vec<WNKey>, WordNetLoadError>
+    where F : FnMut(&str) -> Result<Option<WNKey>, WordNetLoadError> {
+    let mut synsets = Vec::new();
+    for member in class.members.iter() {
+        for sense_key in member.sense_keys.iter() {
+            if let Some(id) = by_sense_key(sense_key)? {
+                if !synsets.contains(&id) {
+                    synsets.push(id);
+                }
+            }
+        }
+    }
+    Ok(synsets)
+}
@@ -1,14 +1,51 @@
 //! Functions for handling the in-memory model of WordNet and loading it form
 //! disk
 //use glosstag::{Gloss,build_glosstags};
-use std::collections::HashMap;
-use crate::links::{Link,LinkType};
+use std::collections::{BTreeMap,HashMap,HashSet};
+use std::str::FromStr;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use crate::links::Link;
 use serde_json;
 use rusqlite;
 use crate::wordnet_model::Synset;
+use crate::fuzzy::{trigrams, edit_distance, max_distance, MAX_CANDIDATES};
+use crate::morphy::detach;
+use crate::normalize::Folding;
+use crate::pronunciation::{PronunciationMatch, clean, rhyme};
+use crate::taxonomy::{max_depths, PathEdge};
+use crate::similarity::{InformationContent, information_content_files};
+use crate::omwn::lemma_language;
+use crate::relations::registry;
+use crate::schemes::Scheme;
+use crate::glosstag::{GlossType, GlossUse};
+use crate::coverage::{Coverage, LanguageCoverage, UncoveredSynset};
+use crate::verbnet::{VerbNetClass, VerbNetClassSynsets};
 
 pub type WNKey=String;
 
+/// The mapping of an identifier from one scheme to another, through the
+/// synsets that it identifies
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct IdMapping {
+    pub from : String,
+    pub to : String,
+    pub key : String,
+    pub synsets : Vec<WNKey>,
+    pub targets : Vec<String>,
+    /// True if the key maps to more than one target
+    pub one_to_many : bool
+}
+
+/// A language that has lemmas in the database, with the number of distinct
+/// lemmas and of senses (lemma-synset pairs)
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct LanguageCount {
+    pub language : String,
+    pub lemmas : u32,
+    pub senses : u32
+}
+
 
 ///// A WordNet Key consisting of 8 digits and a part of speech.
 ///// This data structure stores the value as a 4-byte integer to save memory
@@ -158,11 +195,46 @@ pub struct WordNetBuilder {
     by_ili : HashMap<String, WNKey>,
     by_pwn30 : HashMap<WNKey, WNKey>,
     by_pwn20 : HashMap<WNKey, WNKey>,
-    by_sense_key : HashMap<String, WNKey>
+    by_sense_key : HashMap<String, WNKey>,
+    folding : HashMap<String, Folding>
 }
 
 pub struct WordNet;
 
+/// The folding rules of each language that has been looked up
+static FOLDING_CACHE : Lazy<Mutex<HashMap<String, Folding>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A search for lemmas matching a wildcard pattern, where `?` (or `_`)
+/// matches any single character and `*` matches any sequence of characters
+pub struct PatternQuery<'a> {
+    pub pattern : &'a str,
+    pub language : &'a str,
+    pub pos : Option<&'a str>,
+    pub min_length : Option<u32>,
+    pub max_length : Option<u32>,
+    pub definition : Option<&'a str>,
+    pub limit : u32
+}
+
+fn reverse(s : &str) -> String {
+    s.chars().rev().collect()
+}
+
+/// Convert a wildcard pattern to an SQLite GLOB, escaping the characters
+/// that are special to GLOB but not to the pattern
+fn glob_pattern(pattern : &str) -> String {
+    let mut glob = String::new();
+    for c in pattern.chars() {
+        match c {
+            '[' => glob.push_str("[[]"),
+            ']' => glob.push_str("[]]"),
+            '_' => glob.push('?'),
+            c => glob.push(c)
+        }
+    }
+    glob
+}
+
 fn ok_wordnet_str(s : String) -> Result<String, WordNetLoadError> {
     Ok(s)
 }
@@ -170,21 +242,53 @@ fn ok_wordnet_str(s : String) -> Result<String, WordNetLoadError> {
 impl WordNetBuilder {
     pub fn new() -> Result<WordNetBuilder,WordNetLoadError> {
         let conn = WordNet::open_conn()?;
+        // The definition is lower-cased for searching in pattern queries
         conn.execute("CREATE TABLE synsets (
                       key TEXT NOT NULL,
                       ili TEXT NOT NULL,
+                      definition TEXT NOT NULL,
                       json TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX synsets_key ON synsets (key)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX synsets_ili ON synsets (ili)", rusqlite::NO_PARAMS)?;
+        // The lemma_key is the folded lemma, and form is the folded form
+        // (the lemma or one of its written variants) used by searches
         conn.execute("CREATE TABLE lemmas (
                       key INTEGER PRIMARY KEY,
                       lemma TEXT NOT NULL,
+                      lemma_key TEXT NOT NULL,
                       form TEXT NOT NULL,
+                      form_reversed TEXT NOT NULL,
                       language TEXT NOT NULL,
+                      pos TEXT NOT NULL,
                       synset TEXT NOT NULL,
+                      source TEXT,
                       FOREIGN KEY (synset) REFERENCES synsets (key))", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX lemmas_form ON lemmas (form, language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX lemmas_form_reversed ON lemmas (form_reversed, language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX lemmas_lemma_key ON lemmas (lemma_key, language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX lemmas_language ON lemmas (language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE folding (
+                      language TEXT NOT NULL,
+                      rules TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX lemmas_synset ON lemmas (synset)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE lemma_trigrams (
+                      trigram TEXT NOT NULL,
+                      lemma TEXT NOT NULL,
+                      form TEXT NOT NULL,
+                      language TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX lemma_trigrams_trigram ON lemma_trigrams (trigram, language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE pronunciations (
+                      lemma TEXT NOT NULL,
+                      lemma_key TEXT NOT NULL,
+                      language TEXT NOT NULL,
+                      value TEXT NOT NULL,
+                      variety TEXT,
+                      rhyme TEXT NOT NULL,
+                      synset TEXT NOT NULL,
+                      FOREIGN KEY (synset) REFERENCES synsets (key))", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX pronunciations_lemma_key ON pronunciations (lemma_key, language)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX pronunciations_value ON pronunciations (value)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX pronunciations_rhyme ON pronunciations (rhyme)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE TABLE sense_keys (
                       sense_key TEXT NOT NULL,
                       synset TEXT NOT NULL,
@@ -208,21 +312,78 @@ impl WordNetBuilder {
         conn.execute("CREATE INDEX old_keys_idx ON old_keys (idx)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX old_keys_key ON old_keys (key)", rusqlite::NO_PARAMS)?;
         conn.execute("CREATE INDEX old_keys_synset ON old_keys (synset)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE schemes (
+                      name TEXT NOT NULL,
+                      prefix TEXT NOT NULL,
+                      label TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE morph_exceptions (
+                      form TEXT NOT NULL,
+                      lemma TEXT NOT NULL,
+                      pos TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX morph_exceptions_form ON morph_exceptions (form)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE incoming (
+                      target TEXT NOT NULL,
+                      source TEXT NOT NULL,
+                      type TEXT NOT NULL,
+                      src_word TEXT,
+                      trg_word TEXT)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX incoming_target ON incoming (target, type)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE gloss_uses (
+                      target TEXT NOT NULL,
+                      source TEXT NOT NULL,
+                      gloss_type TEXT NOT NULL,
+                      word TEXT NOT NULL,
+                      start INTEGER NOT NULL,
+                      end INTEGER NOT NULL,
+                      context TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX gloss_uses_target ON gloss_uses (target)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE verbnet_classes (
+                      class TEXT NOT NULL,
+                      json TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX verbnet_classes_class ON verbnet_classes (class)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE verbnet_members (
+                      class TEXT NOT NULL,
+                      synset TEXT NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX verbnet_members_class ON verbnet_members (class)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX verbnet_members_synset ON verbnet_members (synset)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE taxonomy_depths (
+                      pos TEXT NOT NULL,
+                      depth INTEGER NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE TABLE information_content (
+                      name TEXT NOT NULL,
+                      synset TEXT NOT NULL,
+                      value REAL NOT NULL)", rusqlite::NO_PARAMS)?;
+        conn.execute("CREATE INDEX information_content_synset ON information_content (name, synset)", rusqlite::NO_PARAMS)?;
         Ok(WordNetBuilder { 
             conn : conn,
             synsets : HashMap::new(),
             by_ili : HashMap::new(),
             by_pwn30: HashMap::new(),
             by_pwn20: HashMap::new(),
-            by_sense_key : HashMap::new()
+            by_sense_key : HashMap::new(),
+            folding : HashMap::new()
         })
     }
 
+    /// Set the folding rules used to build the lookup keys of lemmas in
+    /// each language. This must be called before any synsets are added
+    pub fn set_folding(&mut self, folding : HashMap<String, Folding>) -> Result<(),WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        for (lang, f) in folding.iter() {
+            tx.execute("INSERT INTO folding (language, rules) VALUES (?, ?)",
+                       &[lang, &f.to_rules()])?;
+        }
+        tx.commit()?;
+        FOLDING_CACHE.lock().unwrap().clear();
+        self.folding = folding;
+        Ok(())
+    }
+
     pub fn set_synsets(&mut self, values : HashMap<WNKey, Synset>) -> Result<(),WordNetLoadError> {
         {
             let tx = self.conn.transaction()?;
             for (k, v) in values.iter() {
-                WordNetBuilder::insert_synset(&tx, k.clone(), v.clone())?;
+                WordNetBuilder::insert_synset(&tx, &self.folding, k.clone(), v.clone())?;
             }
             tx.commit()?;
         }
@@ -250,20 +411,56 @@ impl WordNetBuilder {
         Ok(())
     }
 
+    /// Index the lemmas of another wordnet (e.g., from the Open Multilingual
+    /// Wordnet) for the synsets they translate. The source is the project
+    /// the lemmas were taken from
+    pub fn add_foreign_lemmas(&mut self, language : &str, source : &str,
+                              lemmas : &HashMap<WNKey, Vec<String>>) -> Result<(), WordNetLoadError> {
+        let f = self.folding.get(language).cloned().unwrap_or_default();
+        let tx = self.conn.transaction()?;
+        for (key, values) in lemmas.iter() {
+            if let Some(synset) = self.synsets.get(key) {
+                let pos = synset.pos.to_string();
+                for lemma in values.iter() {
+                    let lemma_key = f.fold(lemma);
+                    tx.execute("INSERT INTO lemmas (lemma, lemma_key, form, form_reversed, language, pos, synset, source) VALUES (?,?,?,?,?,?,?,?)",
+                               &[lemma, &lemma_key, &lemma_key, &reverse(&lemma_key),
+                                 &language.to_string(), &pos, key, &source.to_string()])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     fn insert_synset(tx : &rusqlite::Transaction,
+                     folding : &HashMap<String, Folding>,
                      key : WNKey, synset : Synset) 
           -> Result<(),WordNetLoadError> {
         let key_str = key.to_string();
         let val_str = serde_json::to_string(&synset)?;
-        tx.execute("INSERT INTO synsets (key, ili, json) 
-                      VALUES (?1, ?2, ?3)",
-                     &[&key_str, &synset.ili, &val_str])?;
+        tx.execute("INSERT INTO synsets (key, ili, definition, json) 
+                      VALUES (?1, ?2, ?3, ?4)",
+                     &[&key_str, &synset.ili, &synset.definition.to_lowercase(), &val_str])?;
+        let pos = synset.pos.to_string();
         for sense in synset.lemmas {
-            tx.execute("INSERT INTO lemmas (lemma, form, language, synset) VALUES (?,?,?,?)", 
-                         &[&sense.lemma, &sense.lemma.to_lowercase(), &sense.language, &key_str])?;
+            let f = folding.get(&sense.language).cloned().unwrap_or_default();
+            let lemma_key = f.fold(&sense.lemma);
+            tx.execute("INSERT INTO lemmas (lemma, lemma_key, form, form_reversed, language, pos, synset) VALUES (?,?,?,?,?,?,?)", 
+                         &[&sense.lemma, &lemma_key, &lemma_key, &reverse(&lemma_key),
+                           &sense.language, &pos, &key_str])?;
+            for pron in sense.pronunciations.iter() {
+                tx.execute("INSERT INTO pronunciations (lemma, lemma_key, language, value, variety, rhyme, synset)
+                            VALUES (?,?,?,?,?,?,?)",
+                           &[&sense.lemma as &dyn rusqlite::types::ToSql, &lemma_key,
+                             &sense.language, &clean(&pron.value), &pron.variety,
+                             &rhyme(&pron.value), &key_str])?;
+            }
             for form in sense.forms {
-                tx.execute("INSERT INTO lemmas (lemma, form, language, synset) VALUES (?,?,?,?)",
-                        &[&sense.lemma, &form.to_lowercase(), &sense.language, &key_str])?;
+                let form = f.fold(&form);
+                tx.execute("INSERT INTO lemmas (lemma, lemma_key, form, form_reversed, language, pos, synset) VALUES (?,?,?,?,?,?,?)",
+                        &[&sense.lemma, &lemma_key, &form, &reverse(&form),
+                          &sense.language, &pos, &key_str])?;
             }
             match sense.sense_key {
                 Some(ref sense_key) => {
@@ -282,8 +479,8 @@ impl WordNetBuilder {
             -> Result<(),WordNetLoadError> {
         let key_str = key.to_string();
         let val_str = serde_json::to_string(&synset)?;
-        tx.execute("UPDATE synsets SET json=? WHERE key=?", 
-                     &[&val_str, &key_str])?;
+        tx.execute("UPDATE synsets SET definition=?, json=? WHERE key=?", 
+                     &[&synset.definition.to_lowercase(), &val_str, &key_str])?;
         Ok(())
     }
 
@@ -302,22 +499,32 @@ impl WordNetBuilder {
     }
 
     /// Add a link set to the database
-    pub fn insert_links(&mut self, link_type : LinkType,
-                        values : Vec<(WNKey, String)>) -> Result<(), WordNetLoadError> {
-        for &(ref key, ref target) in values.iter() {
-            if let Some(synset) = self.synsets.get_mut(key) {
-                synset.links.push(Link {
-                    link_type: link_type.clone(),
-                    target: target.clone()
-                })
-            }
-        }
+    pub fn insert_links(&mut self, values : Vec<(WNKey, Link)>) -> Result<(), WordNetLoadError> {
         let tx = self.conn.transaction()?;
-        for (key, target) in values {
+        for (key, link) in values {
             tx.execute("INSERT INTO links VALUES (?1, ?2, ?3)",
                      &[&key.to_string(), 
-                       &serde_json::to_string(&link_type)?, 
-                       &target])?;
+                       &serde_json::to_string(&link.link_type)?, 
+                       &link.target])?;
+            if let Some(synset) = self.synsets.get_mut(&key) {
+                synset.links.push(link);
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add the VerbNet classes with the synsets of their members
+    pub fn insert_verbnet(&mut self, classes : Vec<(&VerbNetClass, Vec<WNKey>)>)
+        -> Result<(), WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        for (class, synsets) in classes {
+            tx.execute("INSERT INTO verbnet_classes VALUES (?, ?)",
+                       &[&class.id, &serde_json::to_string(class)?])?;
+            for synset in synsets {
+                tx.execute("INSERT INTO verbnet_members VALUES (?, ?)",
+                           &[&class.id, &synset])?;
+            }
         }
         tx.commit()?;
         Ok(())
@@ -354,6 +561,26 @@ impl WordNetBuilder {
          Ok(())
     }
 
+    /// Record an identifier scheme, whose keys are added with `set_old_ids`
+    pub fn add_scheme(&mut self, scheme : &Scheme) -> Result<(),WordNetLoadError> {
+        self.conn.execute("INSERT INTO schemes (name, prefix, label) VALUES (?, ?, ?)",
+                          &[&scheme.name, &scheme.prefix, &scheme.label])?;
+        Ok(())
+    }
+
+    /// Add a morphological exception list for a part of speech
+    pub fn set_exceptions(&mut self, pos : &str, values : Vec<(String, String)>)
+        -> Result<(),WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        for (form, lemma) in values {
+            tx.execute("INSERT INTO morph_exceptions (form, lemma, pos)
+                        VALUES (?, ?, ?)",
+                       &[&form, &lemma, &pos.to_string()])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn get_id_by_ili(&self, ili : &str) -> 
         Result<Option<WNKey>,WordNetLoadError> {
         Ok(self.by_ili.get(ili).map(|x| x.clone()))
@@ -374,13 +601,149 @@ impl WordNetBuilder {
         Ok(self.by_pwn30.get(key).map(|x| x.clone()))
     }
 
+    /// The WordNet 3.0 offsets and the synsets they map to
+    pub fn get_pwn30_ids(&self) -> Vec<(WNKey, WNKey)> {
+        self.by_pwn30.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+
     pub fn get_id_by_pwn20(&self, key : &WNKey) ->
         Result<Option<WNKey>,WordNetLoadError> {
         Ok(self.by_pwn20.get(key).map(|x| x.clone()))
     }
 
-    pub fn finalize(&mut self) -> Result<WordNet,WordNetLoadError> { 
+    /// Build the trigram index used for lemma suggestions from all the
+    /// lemmas and forms in the database
+    fn build_trigrams(&mut self) -> Result<(), WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare("SELECT DISTINCT lemma, form, language FROM lemmas")?;
+            let mut res = stmt.query(rusqlite::NO_PARAMS)?;
+            while let Some(r) = res.next()? {
+                let lemma : String = r.get(0)?;
+                let form : String = r.get(1)?;
+                let language : String = r.get(2)?;
+                for trigram in trigrams(&form) {
+                    tx.execute("INSERT INTO lemma_trigrams (trigram, lemma, form, language)
+                                VALUES (?, ?, ?, ?)",
+                               &[&trigram, &lemma, &form, &language])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Report any relations whose type is not in the relation vocabulary or
+    /// which link parts of speech that the type does not allow
+    fn check_relations(&self) {
+        let registry = registry();
+        let mut unknown : HashMap<&str, u32> = HashMap::new();
+        let mut bad_pos : HashMap<&str, u32> = HashMap::new();
+        for synset in self.synsets.values() {
+            for r in synset.relations.iter() {
+                match registry.get(&r.rel_type) {
+                    Some(t) => {
+                        if let Some(target) = self.synsets.get(&r.target) {
+                            if !t.allows(&synset.pos.to_string(), &target.pos.to_string()) {
+                                *bad_pos.entry(&r.rel_type).or_insert(0) += 1;
+                            }
+                        }
+                    },
+                    None => *unknown.entry(&r.rel_type).or_insert(0) += 1
+                }
+            }
+        }
+        for (rel_type, count) in unknown {
+            eprintln!("Unknown relation type {} ({} relations)", rel_type, count);
+        }
+        for (rel_type, count) in bad_pos {
+            eprintln!("Relation type {} links unexpected parts of speech ({} relations)",
+                      rel_type, count);
+        }
+    }
+
+    /// Index every relation by its target, so that the relations pointing
+    /// to a synset can be found
+    fn build_incoming(&mut self) -> Result<(), WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        for synset in self.synsets.values() {
+            for r in synset.relations.iter() {
+                tx.execute("INSERT INTO incoming (target, source, type, src_word, trg_word)
+                            VALUES (?, ?, ?, ?, ?)",
+                           &[&r.target as &dyn rusqlite::types::ToSql, &synset.id,
+                             &r.rel_type, &r.src_word, &r.trg_word])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Index the synsets that are tagged in the glosses of other synsets
+    fn build_gloss_uses(&mut self) -> Result<(), WordNetLoadError> {
+        let tx = self.conn.transaction()?;
+        for synset in self.synsets.values() {
+            for gloss in synset.gloss.iter().flatten() {
+                let context = gloss.text();
+                let gloss_type = gloss.gloss_type.to_string();
+                for (word, (start, end)) in gloss.words.iter().zip(gloss.offsets()) {
+                    for target in word.synset.iter().chain(word.glob.iter()) {
+                        tx.execute("INSERT INTO gloss_uses (target, source, gloss_type, word, start, end, context)
+                                    VALUES (?, ?, ?, ?, ?, ?, ?)",
+                                   &[target as &dyn rusqlite::types::ToSql, &synset.id,
+                                     &gloss_type, &word.text, &(start as u32), &(end as u32),
+                                     &context])?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store the maximum depth of the hypernym hierarchy of each part of
+    /// speech, as used by the Leacock-Chodorow similarity
+    fn build_depths(&mut self) -> Result<(), WordNetLoadError> {
+        let depths = max_depths(&self.synsets);
+        let tx = self.conn.transaction()?;
+        for (pos, depth) in depths {
+            tx.execute("INSERT INTO taxonomy_depths (pos, depth) VALUES (?, ?)",
+                       &[&pos as &dyn rusqlite::types::ToSql, &(depth as u32)])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store the information content of each synset from each of the
+    /// information content files, as used by the Resnik, Lin and
+    /// Jiang-Conrath similarities
+    fn build_information_content(&mut self) -> Result<(), WordNetLoadError> {
+        for (name, path) in information_content_files()? {
+            let ic = InformationContent::load(path)?;
+            let tx = self.conn.transaction()?;
+            for (key, synset) in self.synsets.iter() {
+                if let Some(value) = ic.get(synset) {
+                    tx.execute("INSERT INTO information_content (name, synset, value) VALUES (?, ?, ?)",
+                               &[&name as &dyn rusqlite::types::ToSql, key, &value])?;
+                }
+            }
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    pub fn finalize(&mut self) -> Result<WordNet,WordNetLoadError> {
         self.recommit_synsets()?;
+        eprintln!("Building lemma trigram index");
+        self.build_trigrams()?;
+        self.check_relations();
+        eprintln!("Building incoming relation index");
+        self.build_incoming()?;
+        eprintln!("Building gloss use index");
+        self.build_gloss_uses()?;
+        eprintln!("Computing taxonomy depths");
+        self.build_depths()?;
+        eprintln!("Computing information content");
+        self.build_information_content()?;
         Ok(WordNet)
     }
 }
@@ -408,14 +771,265 @@ impl WordNet {
                              &[&key.to_string()],
                              |s| { serde_json::from_str(&s) })
     }
+    /// The maximum depth of the hypernym hierarchy for a part of speech
+    pub fn taxonomy_depth(&self, pos : &str) -> Result<Option<u32>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare("SELECT depth FROM taxonomy_depths WHERE pos=?")?;
+        let mut res = stmt.query(&[&pos.to_string()])?;
+        match res.next()? {
+            Some(r) => Ok(Some(r.get(0)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Whether information content of a name has been loaded
+    pub fn has_information_content(&self, name : &str) -> Result<bool,WordNetLoadError> {
+        Ok(sqlite_query_opt_map("SELECT name FROM information_content WHERE name=? LIMIT 1",
+                                &[&name.to_string()], ok_wordnet_str)?.is_some())
+    }
+
+    /// The information content of synsets, for those that have a value
+    pub fn information_content(&self, name : &str, keys : &[WNKey])
+            -> Result<HashMap<WNKey, f64>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut result = HashMap::new();
+        for chunk in keys.chunks(500) {
+            let mut stmt = conn.prepare(&format!(
+                    "SELECT synset, value FROM information_content
+                     WHERE name=? AND synset IN ({})",
+                    vec!["?"; chunk.len()].join(",")))?;
+            let mut values : Vec<&dyn rusqlite::types::ToSql> = vec![&name];
+            for k in chunk.iter() {
+                values.push(k);
+            }
+            let mut res = stmt.query(&values)?;
+            while let Some(r) = res.next()? {
+                result.insert(r.get(0)?, r.get(1)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// The folding rules for the lookup keys of a language, which are read
+    /// from the database once for each language
+    pub fn folding(&self, lang : &str) -> Result<Folding,WordNetLoadError> {
+        if let Some(f) = FOLDING_CACHE.lock().unwrap().get(lang) {
+            return Ok(f.clone());
+        }
+        self.folding_in(&WordNet::open_conn()?, lang)
+    }
+
+    fn folding_in(&self, conn : &rusqlite::Connection, lang : &str)
+            -> Result<Folding,WordNetLoadError> {
+        let mut cache = FOLDING_CACHE.lock().unwrap();
+        if let Some(f) = cache.get(lang) {
+            return Ok(f.clone());
+        }
+        let mut stmt = conn.prepare("SELECT rules FROM folding WHERE language=?")?;
+        let mut res = stmt.query(&[&lang.to_string()])?;
+        let folding = match res.next()? {
+            Some(r) => Folding::from_rules(&r.get::<_, String>(0)?)?,
+            None => Folding::default()
+        };
+        cache.insert(lang.to_string(), folding.clone());
+        Ok(folding)
+    }
+
+    /// Get many synsets by their keys using a single connection. Keys that
+    /// are not found are skipped
+    pub fn get_synsets_by_ids(&self, keys : &[WNKey]) -> Result<Vec<Synset>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut result = Vec::new();
+        for chunk in keys.chunks(500) {
+            let mut stmt = conn.prepare(&format!(
+                    "SELECT json FROM synsets WHERE key IN ({})",
+                    vec!["?"; chunk.len()].join(",")))?;
+            let mut res = stmt.query(chunk)?;
+            while let Some(r) = res.next()? {
+                let json : String = r.get(0)?;
+                result.push(serde_json::from_str(&json)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// The relations that point to any of the given synsets, optionally
+    /// only of one type
+    pub fn get_incoming(&self, keys : &[WNKey], rel_type : Option<&str>)
+            -> Result<Vec<PathEdge>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut result = Vec::new();
+        for chunk in keys.chunks(500) {
+            let mut values : Vec<&dyn rusqlite::types::ToSql> = chunk.iter()
+                .map(|k| k as &dyn rusqlite::types::ToSql)
+                .collect();
+            let mut query = format!(
+                "SELECT source, target, type, src_word, trg_word FROM incoming
+                 WHERE target IN ({})", vec!["?"; chunk.len()].join(","));
+            if let Some(ref t) = rel_type {
+                query.push_str(" AND type=?");
+                values.push(t);
+            }
+            let mut stmt = conn.prepare(&query)?;
+            let mut res = stmt.query(&values)?;
+            while let Some(r) = res.next()? {
+                result.push(PathEdge {
+                    source: r.get(0)?,
+                    target: r.get(1)?,
+                    rel_type: r.get(2)?,
+                    src_word: r.get(3)?,
+                    trg_word: r.get(4)?
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    /// A VerbNet class with the synsets of its members, including the members
+    /// of its subclasses
+    pub fn get_verbnet_class(&self, class : &str) -> Result<Option<VerbNetClassSynsets>,WordNetLoadError> {
+        let mut class = match self.verbnet_class_by_id(class)? {
+            Some(class) => class,
+            None => return Ok(None)
+        };
+        // The members of the subclasses are also members of the class
+        let mut ids : Vec<WNKey> = Vec::new();
+        let mut pending = vec![class.id.clone()];
+        while let Some(id) = pending.pop() {
+            for synset in sqlite_query_vec("SELECT synset FROM verbnet_members WHERE class=?",
+                                           &[&id], ok_wnkey)? {
+                if !ids.contains(&synset) {
+                    ids.push(synset);
+                }
+            }
+            if id == class.id {
+                pending.extend(class.subclasses.iter().cloned());
+            } else if let Some(subclass) = self.verbnet_class_by_id(&id)? {
+                class.members.extend(subclass.members);
+                pending.extend(subclass.subclasses);
+            }
+        }
+        Ok(Some(VerbNetClassSynsets {
+            synsets: self.get_synsets_by_ids(&ids)?,
+            class
+        }))
+    }
+
+    fn verbnet_class_by_id(&self, class : &str) -> Result<Option<VerbNetClass>,WordNetLoadError> {
+        match sqlite_query_opt_map("SELECT json FROM verbnet_classes WHERE class=?",
+                                   &[&class.to_string()], ok_wordnet_str)? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// The VerbNet classes that a synset is a member of
+    pub fn get_verbnet_classes(&self, key : &WNKey) -> Result<Vec<VerbNetClass>,WordNetLoadError> {
+        let jsons = sqlite_query_vec("SELECT json FROM verbnet_classes
+                                      JOIN verbnet_members ON verbnet_members.class=verbnet_classes.class
+                                      WHERE synset=? ORDER BY verbnet_classes.class",
+                                     &[key], ok_wordnet_str)?;
+        let mut classes = Vec::new();
+        for json in jsons {
+            classes.push(serde_json::from_str(&json)?);
+        }
+        Ok(classes)
+    }
+
+    /// The uses of a synset in the glosses of other synsets
+    pub fn get_gloss_uses(&self, key : &WNKey) -> Result<Vec<GlossUse>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare("SELECT source, gloss_type, word, start, end, context
+                                     FROM gloss_uses WHERE target=? ORDER BY source, start")?;
+        let mut res = stmt.query(&[key])?;
+        let mut data = Vec::new();
+        while let Some(r) = res.next()? {
+            let gloss_type : String = r.get(1)?;
+            data.push(GlossUse {
+                synset: r.get(0)?,
+                gloss_type: GlossType::from_str(&gloss_type)?,
+                word: r.get(2)?,
+                start: r.get(3)?,
+                end: r.get(4)?,
+                context: r.get(5)?
+            });
+        }
+        Ok(data)
+    }
+
+    /// Find the synsets of a lemma, matching the lookup key of the lemma
+    /// under the folding rules of the language (so that "cafe" may find
+    /// "Café")
     pub fn get_by_lemma(&self, lemma : &str, lang : &str) -> Result<Vec<Synset>,WordNetLoadError> { 
-        sqlite_query_vec("SELECT DISTINCT json FROM synsets
+        let conn = WordNet::open_conn()?;
+        let key = self.folding_in(&conn, lang)?.fold(lemma);
+        WordNet::get_by_lemma_key(&conn, &key, lang)
+    }
+
+    fn get_by_lemma_key(conn : &rusqlite::Connection, key : &str, lang : &str)
+            -> Result<Vec<Synset>,WordNetLoadError> {
+        let mut stmt = conn.prepare("SELECT DISTINCT json FROM synsets
                           JOIN lemmas ON lemmas.synset=synsets.key
                           LEFT JOIN sense_keys ON sense_keys.lemma == lemmas.lemma AND sense_keys.synset == synsets.key
-                          WHERE lemmas.lemma=? AND language=?
-                          ORDER BY sense_keys.importance",
-                          &[&lemma.to_owned(), &lang.to_owned()],
-                          |s| { serde_json::from_str(&s) })
+                          WHERE lemmas.lemma_key=? AND language=?
+                          ORDER BY sense_keys.importance")?;
+        let mut res = stmt.query(&[key, lang])?;
+        let mut data = Vec::new();
+        while let Some(r) = res.next()? {
+            let json : String = r.get(0)?;
+            data.push(serde_json::from_str(&json)?);
+        }
+        Ok(data)
+    }
+    /// Find the synsets of an inflected form by its base forms. The base
+    /// forms are found from the exception lists and the detachment rules, and
+    /// only those that are lemmas of a synset of the matching part of speech
+    /// are returned
+    pub fn get_by_inflected_form(&self, form : &str, lang : &str)
+            -> Result<(Vec<String>, Vec<Synset>),WordNetLoadError> {
+        self.get_by_inflected_form_in(&WordNet::open_conn()?, form, lang)
+    }
+
+    fn get_by_inflected_form_in(&self, conn : &rusqlite::Connection, form : &str, lang : &str)
+            -> Result<(Vec<String>, Vec<Synset>),WordNetLoadError> {
+        let folding = self.folding_in(conn, lang)?;
+        let form = folding.fold(form);
+        let mut candidates = Vec::new();
+        {
+            let mut stmt = conn.prepare("SELECT lemma, pos FROM morph_exceptions
+                                         WHERE form=?")?;
+            let mut res = stmt.query(&[&form])?;
+            while let Some(r) = res.next()? {
+                let lemma : String = r.get(0)?;
+                let pos : String = r.get(1)?;
+                candidates.push((lemma, pos));
+            }
+        }
+        for pos in ["n", "v", "a", "r"].iter() {
+            for base in detach(&form, pos) {
+                candidates.push((base, pos.to_string()));
+            }
+        }
+        let mut base_forms : Vec<String> = Vec::new();
+        let mut synsets : Vec<Synset> = Vec::new();
+        for (base, pos) in candidates {
+            if base == form {
+                continue;
+            }
+            for synset in WordNet::get_by_lemma_key(conn, &folding.fold(&base), lang)? {
+                let synset_pos = match synset.pos.to_string().as_str() {
+                    "s" => "a".to_string(),
+                    p => p.to_string()
+                };
+                if synset_pos == pos && !synsets.iter().any(|s| s.id == synset.id) {
+                    if !base_forms.contains(&base) {
+                        base_forms.push(base.clone());
+                    }
+                    synsets.push(synset);
+                }
+            }
+        }
+        Ok((base_forms, synsets))
     }
 //    pub fn get_id_by_ili(&self, ili : &str) -> Result<Option<WNKey>,WordNetLoadError> {
 //        sqlite_query_opt_map("SELECT key FROM synsets WHERE ili=?",
@@ -427,6 +1041,13 @@ impl WordNet {
                              &[&ili.to_string()],
                              |s| { serde_json::from_str(&s) })
     }
+    /// All the synsets linked to an ILI, e.g., in the lexicons of different
+    /// languages
+    pub fn get_all_by_ili(&self, ili : &str) -> Result<Vec<Synset>,WordNetLoadError> {
+        sqlite_query_vec("SELECT json FROM synsets WHERE ili=?",
+                         &[&ili.to_string()],
+                         |s| { serde_json::from_str(&s) })
+    }
 //    pub fn get_id_by_sense_key(&self, sense_key : &str) -> Result<Option<WNKey>,WordNetLoadError> {
 //        sqlite_query_opt_map("SELECT synset FROM sense_keys WHERE sense_key=?",
 //                             &[&sense_key.to_string()],
@@ -434,7 +1055,7 @@ impl WordNet {
 //    }
     pub fn get_by_sense_key(&self, sense_key : &str) -> Result<Option<Synset>,WordNetLoadError> {
         sqlite_query_opt_map("SELECT json FROM synsets
-                              JOIN sense_keys ON sense_keys.synse=synsets.key
+                              JOIN sense_keys ON sense_keys.synset=synsets.key
                               WHERE sense_key=?",
                              &[&sense_key.to_string()],
                              |s| { serde_json::from_str(&s) })
@@ -453,6 +1074,201 @@ impl WordNet {
                              |s| { serde_json::from_str(&s) })
     }
 
+    /// Look up many keys in many indexes using a single connection. The
+    /// queries are pairs of an index (as for `get_synsets`) and a key, and
+    /// the result gives the synsets found for each index and key. English
+    /// lemmas that are not found are looked up by their base forms
+    pub fn get_batch(&self, queries : &[(String, String)])
+            -> Result<HashMap<String, HashMap<String, Vec<Synset>>>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut by_index : HashMap<&str, Vec<&str>> = HashMap::new();
+        for (index, key) in queries.iter() {
+            by_index.entry(index.as_str()).or_default().push(key.as_str());
+        }
+        let mut result = HashMap::new();
+        for (index, keys) in by_index {
+            // Lemmas are queried by their folded keys and mapped back
+            let lang = lemma_language(index);
+            let folding = match lang {
+                Some(l) => Some(self.folding_in(&conn, l)?),
+                None => None
+            };
+            let mut query_keys : HashMap<String, Vec<&str>> = HashMap::new();
+            for key in keys {
+                let k = folding.as_ref().map(|f| f.fold(key)).unwrap_or_else(|| key.to_string());
+                query_keys.entry(k).or_default().push(key);
+            }
+            let (query, extra) : (&str, Option<&str>) = if index == "id" {
+                ("SELECT key, json FROM synsets WHERE key IN ({})", None)
+            } else if index == "ili" {
+                ("SELECT ili, json FROM synsets WHERE ili IN ({})", None)
+            } else if index == "sense_key" {
+                ("SELECT sense_keys.sense_key, json FROM synsets
+                  JOIN sense_keys ON sense_keys.synset=synsets.key
+                  WHERE sense_keys.sense_key IN ({})", None)
+            } else if let Some(lang) = lang {
+                ("SELECT DISTINCT lemmas.lemma_key, json, sense_keys.importance FROM synsets
+                  JOIN lemmas ON lemmas.synset=synsets.key
+                  LEFT JOIN sense_keys ON sense_keys.lemma == lemmas.lemma AND sense_keys.synset == synsets.key
+                  WHERE lemmas.lemma_key IN ({}) AND language=?
+                  ORDER BY sense_keys.importance", Some(lang))
+            } else {
+                ("SELECT old_keys.key, json FROM synsets
+                  JOIN old_keys ON old_keys.synset=synsets.key
+                  WHERE old_keys.key IN ({}) AND idx=?", Some(index))
+            };
+            let all_keys : Vec<String> = query_keys.keys().cloned().collect();
+            let found : &mut HashMap<String, Vec<Synset>> = result.entry(index.to_string())
+                .or_default();
+            for chunk in all_keys.chunks(500) {
+                let mut stmt = conn.prepare(&query.replace("{}",
+                        &vec!["?"; chunk.len()].join(",")))?;
+                let mut values : Vec<&dyn rusqlite::types::ToSql> = chunk.iter()
+                    .map(|k| k as &dyn rusqlite::types::ToSql)
+                    .collect();
+                if let Some(ref e) = extra {
+                    values.push(e);
+                }
+                let mut res = stmt.query(&values)?;
+                while let Some(r) = res.next()? {
+                    let key : String = r.get(0)?;
+                    let json : String = r.get(1)?;
+                    let synset : Synset = serde_json::from_str(&json)?;
+                    for k in query_keys.get(&key).into_iter().flatten() {
+                        // A synset is found once for each of its senses
+                        let synsets = found.entry(k.to_string()).or_default();
+                        if !synsets.iter().any(|s| s.id == synset.id) {
+                            synsets.push(synset.clone());
+                        }
+                    }
+                }
+            }
+            // As for single lookups, English lemmas that are not found are
+            // looked up by their base forms
+            if lang == Some("en") {
+                for key in query_keys.values().flatten() {
+                    if !found.contains_key(*key) {
+                        let (_, synsets) = self.get_by_inflected_form_in(&conn, key, "en")?;
+                        if !synsets.is_empty() {
+                            found.insert(key.to_string(), synsets);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// The identifier schemes that are mapped to synsets
+    pub fn get_schemes(&self) -> Result<Vec<Scheme>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare("SELECT name, prefix, label FROM schemes")?;
+        let mut res = stmt.query(rusqlite::NO_PARAMS)?;
+        let mut data = Vec::new();
+        while let Some(r) = res.next()? {
+            data.push(Scheme { name: r.get(0)?, prefix: r.get(1)?, label: r.get(2)?,
+                                path: String::new() });
+        }
+        Ok(data)
+    }
+
+    /// The name of the identifier scheme served at a URL prefix
+    pub fn get_scheme_by_prefix(&self, prefix : &str) -> Result<Option<String>,WordNetLoadError> {
+        sqlite_query_opt_map("SELECT name FROM schemes WHERE prefix=?",
+                             &[&prefix.to_string()], ok_wordnet_str)
+    }
+
+    /// Whether a name is an identifier scheme that can be used by `map_ids`,
+    /// that is `id`, `ili`, `sense_key` or one of the schemes
+    pub fn is_id_scheme(&self, scheme : &str) -> Result<bool,WordNetLoadError> {
+        Ok(scheme == "id" || scheme == "ili" || scheme == "sense_key" ||
+           self.get_schemes()?.iter().any(|s| s.name == scheme))
+    }
+
+    /// Map keys from one identifier scheme to another. A key may map to
+    /// several targets, e.g., a synset has a sense key for each of its
+    /// lemmas (see `is_id_scheme`)
+    pub fn map_ids(&self, from : &str, to : &str, keys : &[String])
+            -> Result<Vec<IdMapping>,WordNetLoadError> {
+        let schemes : Vec<String> = self.get_schemes()?.into_iter().map(|s| s.name).collect();
+        // The queries give pairs of a key in the scheme and a synset when
+        // resolving, and of a synset and a key in the scheme otherwise
+        let scheme_query = |scheme : &str, resolve : bool| -> Option<&'static str> {
+            if scheme == "id" {
+                Some("SELECT key, key FROM synsets WHERE key IN ({})")
+            } else if scheme == "ili" {
+                Some(if resolve { "SELECT ili, key FROM synsets WHERE ili IN ({})" }
+                     else { "SELECT key, ili FROM synsets WHERE key IN ({})" })
+            } else if scheme == "sense_key" {
+                Some(if resolve { "SELECT sense_key, synset FROM sense_keys WHERE sense_key IN ({})" }
+                     else { "SELECT synset, sense_key FROM sense_keys WHERE synset IN ({})" })
+            } else if schemes.iter().any(|s| s == scheme) {
+                Some(if resolve { "SELECT key, synset FROM old_keys WHERE key IN ({}) AND idx=?" }
+                     else { "SELECT synset, key FROM old_keys WHERE synset IN ({}) AND idx=?" })
+            } else {
+                None
+            }
+        };
+        fn query_pairs(conn : &rusqlite::Connection, query : &str, keys : &[String],
+                       scheme : Option<&str>)
+                -> Result<HashMap<String, Vec<String>>,WordNetLoadError> {
+            let scheme = scheme.map(|s| s.to_string());
+            let mut data : HashMap<String, Vec<String>> = HashMap::new();
+            for chunk in keys.chunks(500) {
+                let mut stmt = conn.prepare(&query.replace("{}",
+                        &vec!["?"; chunk.len()].join(",")))?;
+                let mut values : Vec<&dyn rusqlite::types::ToSql> = chunk.iter()
+                    .map(|k| k as &dyn rusqlite::types::ToSql)
+                    .collect();
+                if let Some(ref s) = scheme {
+                    values.push(s);
+                }
+                let mut res = stmt.query(&values)?;
+                while let Some(r) = res.next()? {
+                    let values = data.entry(r.get(0)?).or_default();
+                    let s : String = r.get(1)?;
+                    if !values.contains(&s) {
+                        values.push(s);
+                    }
+                }
+            }
+            Ok(data)
+        }
+        let resolve = scheme_query(from, true)
+            .ok_or(WordNetLoadError::Schema("Unknown identifier scheme"))?;
+        let target = scheme_query(to, false)
+            .ok_or(WordNetLoadError::Schema("Unknown identifier scheme"))?;
+        // Only the schemes in the old keys table need the index as a parameter
+        let from_idx = if schemes.iter().any(|s| s == from) { Some(from) } else { None };
+        let to_idx = if schemes.iter().any(|s| s == to) { Some(to) } else { None };
+        let conn = WordNet::open_conn()?;
+        let resolved = query_pairs(&conn, resolve, keys, from_idx)?;
+        let synset_keys : Vec<String> = resolved.values().flatten().cloned()
+            .collect::<HashSet<String>>().into_iter().collect();
+        let mapped = query_pairs(&conn, target, &synset_keys, to_idx)?;
+        let mut result = Vec::new();
+        for key in keys {
+            let synsets = resolved.get(key).cloned().unwrap_or_default();
+            let mut targets = Vec::new();
+            for synset in synsets.iter() {
+                for t in mapped.get(synset).into_iter().flatten() {
+                    if !targets.contains(t) {
+                        targets.push(t.clone());
+                    }
+                }
+            }
+            result.push(IdMapping {
+                from: from.to_string(),
+                to: to.to_string(),
+                key: key.clone(),
+                synsets,
+                one_to_many: targets.len() > 1,
+                targets
+            });
+        }
+        Ok(result)
+    }
+
     pub fn list_by_id(&self, key : &WNKey, 
                       limit : u32) -> Result<Vec<WNKey>,WordNetLoadError> {
         sqlite_query_vec("SELECT DISTINCT key FROM synsets
@@ -464,16 +1280,273 @@ impl WordNet {
     }
     pub fn list_by_lemma(&self, lemma : &str, language : &str,
                           limit : u32) -> Result<Vec<String>,WordNetLoadError> {
+        let key = self.folding(language)?.fold(lemma);
         sqlite_query_vec("SELECT DISTINCT lemma FROM lemmas
                           WHERE form >= ? and form like ? and language=?
                           ORDER BY form
                           LIMIT ?",
-                         &[&lemma.to_lowercase(), 
-                            &(lemma.to_lowercase() + "%"),
+                         &[&key, 
+                            &(key.clone() + "%"),
                             &language.to_string(),
                          &limit], 
                          ok_wordnet_str)
     }
+
+    /// The languages that have lemmas in the database
+    pub fn get_languages(&self) -> Result<Vec<LanguageCount>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare("SELECT language, COUNT(DISTINCT lemma),
+                                     COUNT(DISTINCT lemma || ' ' || synset)
+                                     FROM lemmas GROUP BY language ORDER BY language")?;
+        let mut res = stmt.query(rusqlite::NO_PARAMS)?;
+        let mut data = Vec::new();
+        while let Some(r) = res.next()? {
+            data.push(LanguageCount { language: r.get(0)?, lemmas: r.get(1)?, senses: r.get(2)? });
+        }
+        Ok(data)
+    }
+
+    /// The coverage of each language, that is the synsets that have lemmas
+    /// in the language, the lemmas and senses for each part of speech, the
+    /// proportion of the core concepts covered and the uncovered synsets with
+    /// the most hyponyms
+    pub fn coverage(&self, core : Option<&[WNKey]>, limit : u32) -> Result<Coverage,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let synsets : u32 = conn.query_row("SELECT COUNT(*) FROM synsets",
+                                           rusqlite::NO_PARAMS, |r| r.get(0))?;
+        let mut languages = Vec::new();
+        {
+            let mut stmt = conn.prepare("SELECT language, COUNT(DISTINCT synset) FROM lemmas
+                                         GROUP BY language ORDER BY language")?;
+            let mut res = stmt.query(rusqlite::NO_PARAMS)?;
+            while let Some(r) = res.next()? {
+                let covered : u32 = r.get(1)?;
+                languages.push(LanguageCoverage {
+                    language: r.get(0)?,
+                    synsets: covered,
+                    coverage: if synsets > 0 { covered as f64 / synsets as f64 } else { 0.0 },
+                    lemmas: BTreeMap::new(),
+                    senses: BTreeMap::new(),
+                    core_coverage: None,
+                    uncovered: Vec::new()
+                });
+            }
+        }
+        let mut stmt = conn.prepare("SELECT language, pos, COUNT(DISTINCT lemma),
+                                     COUNT(DISTINCT lemma || ' ' || synset)
+                                     FROM lemmas GROUP BY language, pos")?;
+        let mut res = stmt.query(rusqlite::NO_PARAMS)?;
+        while let Some(r) = res.next()? {
+            let language : String = r.get(0)?;
+            if let Some(l) = languages.iter_mut().find(|l| l.language == language) {
+                l.lemmas.insert(r.get(1)?, r.get(2)?);
+                l.senses.insert(r.get(1)?, r.get(3)?);
+            }
+        }
+        if let Some(core) = core {
+            let mut counts : HashMap<String, u32> = HashMap::new();
+            for chunk in core.chunks(500) {
+                let mut stmt = conn.prepare(&format!("SELECT language, COUNT(DISTINCT synset)
+                                                      FROM lemmas WHERE synset IN ({})
+                                                      GROUP BY language",
+                                                     vec!["?"; chunk.len()].join(",")))?;
+                let values : Vec<&dyn rusqlite::types::ToSql> = chunk.iter()
+                    .map(|k| k as &dyn rusqlite::types::ToSql).collect();
+                let mut res = stmt.query(&values)?;
+                while let Some(r) = res.next()? {
+                    let n : u32 = r.get(1)?;
+                    *counts.entry(r.get(0)?).or_insert(0) += n;
+                }
+            }
+            for l in languages.iter_mut() {
+                let n = counts.get(&l.language).cloned().unwrap_or(0);
+                l.core_coverage = Some(if core.is_empty() { 0.0 } else { n as f64 / core.len() as f64 });
+            }
+        }
+        let mut stmt = conn.prepare("SELECT target, COUNT(DISTINCT source) AS hyponyms FROM incoming
+                                     WHERE type IN ('hypernym', 'instance_hypernym')
+                                     AND target NOT IN (SELECT synset FROM lemmas WHERE language=?)
+                                     GROUP BY target ORDER BY hyponyms DESC, target LIMIT ?")?;
+        for l in languages.iter_mut() {
+            let mut res = stmt.query(&[&l.language as &dyn rusqlite::types::ToSql, &limit])?;
+            while let Some(r) = res.next()? {
+                l.uncovered.push(UncoveredSynset { id: r.get(0)?, hyponyms: r.get(1)? });
+            }
+        }
+        Ok(Coverage {
+            synsets,
+            core: core.map(|c| c.len() as u32),
+            languages
+        })
+    }
+
+    /// Whether any lemmas are loaded for a language
+    pub fn has_language(&self, language : &str) -> Result<bool,WordNetLoadError> {
+        Ok(sqlite_query_opt_map("SELECT language FROM lemmas WHERE language=? LIMIT 1",
+                                &[&language.to_string()], ok_wordnet_str)?.is_some())
+    }
+
+    /// Find the lemmas whose forms match a wildcard pattern. If the pattern
+    /// starts with a wildcard (and does not end with one) the search is
+    /// made over the reversed forms, so that the index can still be used
+    pub fn list_by_pattern(&self, query : &PatternQuery) -> Result<Vec<String>,WordNetLoadError> {
+        let pattern = self.folding(query.language)?.fold(query.pattern);
+        let is_wildcard = |c : Option<char>| c == Some('*') || c == Some('?') || c == Some('_');
+        let (column, glob) = if is_wildcard(pattern.chars().next())
+            && !is_wildcard(pattern.chars().next_back()) {
+            ("form_reversed", glob_pattern(&reverse(&pattern)))
+        } else {
+            ("form", glob_pattern(&pattern))
+        };
+        let join = if query.definition.is_some() {
+            "JOIN synsets ON synsets.key=lemmas.synset"
+        } else {
+            ""
+        };
+        let mut sql = format!("SELECT lemma FROM lemmas {}
+                               WHERE {} GLOB ? AND language=?", join, column);
+        let mut values : Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+            Box::new(glob), Box::new(query.language.to_string())];
+        if let Some(pos) = query.pos {
+            if pos == "a" || pos == "s" {
+                sql.push_str(" AND pos IN ('a', 's')");
+            } else {
+                sql.push_str(" AND pos=?");
+                values.push(Box::new(pos.to_string()));
+            }
+        }
+        if let Some(min_length) = query.min_length {
+            sql.push_str(" AND length(form) >= ?");
+            values.push(Box::new(min_length));
+        }
+        if let Some(max_length) = query.max_length {
+            sql.push_str(" AND length(form) <= ?");
+            values.push(Box::new(max_length));
+        }
+        if let Some(definition) = query.definition {
+            sql.push_str(" AND instr(synsets.definition, ?) > 0");
+            values.push(Box::new(definition.to_lowercase()));
+        }
+        sql.push_str(" GROUP BY lemma ORDER BY MIN(form) LIMIT ?");
+        values.push(Box::new(query.limit));
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let mut res = stmt.query(values.iter().map(|v| v.as_ref()).collect::<Vec<_>>())?;
+        let mut lemmas : Vec<String> = Vec::new();
+        while let Some(r) = res.next()? {
+            lemmas.push(r.get(0)?);
+        }
+        Ok(lemmas)
+    }
+
+    fn query_pronunciations(&self, sql : &str, values : &[&dyn rusqlite::types::ToSql])
+            -> Result<Vec<PronunciationMatch>,WordNetLoadError> {
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare(sql)?;
+        let mut res = stmt.query(values)?;
+        let mut result = Vec::new();
+        while let Some(r) = res.next()? {
+            result.push(PronunciationMatch {
+                lemma: r.get(0)?,
+                value: r.get(1)?,
+                variety: r.get(2)?
+            });
+        }
+        Ok(result)
+    }
+
+    /// Find the lemmas that rhyme with a lemma, i.e., share the final
+    /// stressed vowel and all that follows it
+    pub fn get_rhymes(&self, lemma : &str, lang : &str, variety : Option<&str>,
+                      limit : u32) -> Result<Vec<PronunciationMatch>,WordNetLoadError> {
+        let key = self.folding(lang)?.fold(lemma);
+        self.query_pronunciations("SELECT DISTINCT p2.lemma, p2.value, p2.variety
+                                   FROM pronunciations AS p1
+                                   JOIN pronunciations AS p2 ON p1.rhyme=p2.rhyme
+                                   WHERE p1.lemma_key=? AND p1.language=?2
+                                   AND p2.language=?2 AND p2.lemma_key != p1.lemma_key
+                                   AND p1.rhyme != ''
+                                   AND (?3 IS NULL OR (p1.variety=?3 AND p2.variety=?3))
+                                   ORDER BY p2.lemma_key
+                                   LIMIT ?4",
+                                  &[&key as &dyn rusqlite::types::ToSql, &lang, &variety, &limit])
+    }
+
+    /// Find the lemmas that are pronounced identically to a lemma
+    pub fn get_homophones(&self, lemma : &str, lang : &str, variety : Option<&str>,
+                          limit : u32) -> Result<Vec<PronunciationMatch>,WordNetLoadError> {
+        let key = self.folding(lang)?.fold(lemma);
+        self.query_pronunciations("SELECT DISTINCT p2.lemma, p2.value, p2.variety
+                                   FROM pronunciations AS p1
+                                   JOIN pronunciations AS p2 ON p1.value=p2.value
+                                   WHERE p1.lemma_key=? AND p1.language=?2
+                                   AND p2.language=?2 AND p2.lemma_key != p1.lemma_key
+                                   AND (?3 IS NULL OR (p1.variety=?3 AND p2.variety=?3))
+                                   ORDER BY p2.lemma_key
+                                   LIMIT ?4",
+                                  &[&key as &dyn rusqlite::types::ToSql, &lang, &variety, &limit])
+    }
+
+    /// Find the lemmas whose pronunciation matches a wildcard pattern over
+    /// IPA, as in `list_by_pattern`
+    pub fn list_by_ipa(&self, pattern : &str, lang : &str, variety : Option<&str>,
+                       limit : u32) -> Result<Vec<PronunciationMatch>,WordNetLoadError> {
+        self.query_pronunciations("SELECT DISTINCT lemma, value, variety FROM pronunciations
+                                   WHERE value GLOB ? AND language=?2
+                                   AND (?3 IS NULL OR variety=?3)
+                                   ORDER BY lemma_key
+                                   LIMIT ?4",
+                                  &[&glob_pattern(&clean(pattern)) as &dyn rusqlite::types::ToSql,
+                                    &lang, &variety, &limit])
+    }
+
+    /// Suggest lemmas close to a lemma that was not found, ranked by edit
+    /// distance and then by the number of shared trigrams
+    pub fn suggest_lemmas(&self, lemma : &str, language : &str,
+                          limit : u32) -> Result<Vec<String>,WordNetLoadError> {
+        let query = self.folding(language)?.fold(lemma);
+        let grams = trigrams(&query);
+        if grams.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = WordNet::open_conn()?;
+        let mut stmt = conn.prepare(&format!(
+                "SELECT lemma, form, COUNT(*) AS shared FROM lemma_trigrams
+                 WHERE language=? AND trigram IN ({})
+                 GROUP BY lemma, form
+                 ORDER BY COUNT(*) DESC, ABS(LENGTH(form) - ?)
+                 LIMIT ?", vec!["?"; grams.len()].join(",")))?;
+        let length = query.chars().count() as u32;
+        let mut values : Vec<&dyn rusqlite::types::ToSql> = vec![&language];
+        for g in grams.iter() {
+            values.push(g);
+        }
+        values.push(&length);
+        values.push(&MAX_CANDIDATES);
+        let mut res = stmt.query(&values)?;
+        let max_dist = max_distance(&query);
+        let mut candidates = Vec::new();
+        while let Some(r) = res.next()? {
+            let lemma : String = r.get(0)?;
+            let form : String = r.get(1)?;
+            let shared : u32 = r.get(2)?;
+            let dist = edit_distance(&query, &form);
+            if dist <= max_dist {
+                candidates.push((dist, shared, lemma));
+            }
+        }
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        let mut suggestions : Vec<String> = Vec::new();
+        for (_, _, lemma) in candidates {
+            if !suggestions.contains(&lemma) {
+                suggestions.push(lemma);
+            }
+            if suggestions.len() >= limit as usize {
+                break;
+            }
+        }
+        Ok(suggestions)
+    }
     pub fn list_by_ili(&self, ili : &str,
                         limit : u32) -> Result<Vec<String>,WordNetLoadError> {
         sqlite_query_vec("SELECT DISTINCT ili FROM synsets
@@ -554,3 +1627,16 @@ quick_error! {
 //        }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_pattern() {
+        assert_eq!(glob_pattern("c?t"), "c?t");
+        assert_eq!(glob_pattern("c_t"), "c?t");
+        assert_eq!(glob_pattern("*ology"), "*ology");
+        assert_eq!(glob_pattern("a[b]"), "a[[]b[]]");
+    }
+}
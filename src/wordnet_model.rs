@@ -98,12 +98,22 @@ pub struct Synset {
     pub lemmas : Vec<Sense>,
     pub id : WNKey,
     pub ili : String,
+    /// The language of the lexicon of the synset, which is the language of
+    /// its definition and examples
+    #[serde(default)]
+    pub language : String,
     pub pos : PartOfSpeech,
     pub subject : String,
     pub relations : Vec<Relation>,
     pub old_keys : HashMap<String, Vec<WNKey>>,
     pub gloss : Option<Vec<Gloss>>,
     pub foreign : HashMap<String, Vec<String>>,
+    /// The definitions in other languages, by language
+    #[serde(default)]
+    pub foreign_definitions : HashMap<String, Vec<String>>,
+    /// The examples in other languages, by language
+    #[serde(default)]
+    pub foreign_examples : HashMap<String, Vec<String>>,
     pub links : Vec<Link>
 }
 
@@ -1,16 +1,19 @@
 //! Code for loading wordnets from disk
-use crate::omwn::load_omwn;
+use crate::omwn::{load_omwn, language_code, read_languages, default_languages, OMW_LANGUAGES_FILE};
 use std::collections::HashMap;
 use std::fs::{File};
 use std::io::{BufRead,BufReader};
 use std::path::Path;
 use xml::reader::{EventReader, XmlEvent};
-use crate::links::{load_links};
+use crate::links::{load_links,load_link_sources};
 use crate::wordnet::{WordNetLoadError,WordNetBuilder,WNKey, WordNet};
 use crate::wordnet_model::{Sense,Synset,Relation,PartOfSpeech,Pronunciation};
 use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
 use crate::glosstag::build_glosstags;
+use crate::morphy::{EXCEPTION_FILES, read_exceptions};
+use crate::normalize::{FOLDING_FILE, default_folding, read_folding};
+use crate::schemes::{configured_schemes,scheme_path};
 
 
 fn unmap_sense_key(sk : &str) -> String {
@@ -38,41 +41,59 @@ fn clean_id(s : &str) -> Result<WNKey, WordNetLoadError> {
     }
 }
 
-pub struct LoadConfiguration {
+pub struct LoadConfiguration<'a> {
     tabs : bool,
-    glosstags : bool,
+    /// The directory of the gloss tag corpus, if it is to be loaded
+    glosstags : Option<&'a str>,
     omwn : bool,
-    links : bool
+    /// Whether the OMW definitions and examples are loaded without the
+    /// lemmas, for wordnets that already have lemmas in these languages
+    omwn_definitions : bool,
+    /// Whether the links of the built-in sources are loaded. The declared
+    /// link sources are always loaded
+    links : bool,
+    exceptions : bool
 }
 
 /// Load a Princeton WordNet-style GWN XML file and associated elements into
 /// the database
-pub fn load_pwn<P : AsRef<Path>>(path : P) -> Result<WordNet, WordNetLoadError> {
+pub fn load_pwn<P : AsRef<Path>>(path : P, glosstags : Option<&str>)
+        -> Result<WordNet, WordNetLoadError> {
     load(path, &LoadConfiguration {
         tabs: true,
-        glosstags: false,
+        glosstags,
         omwn: true,
-        links : true
+        omwn_definitions: false,
+        links : true,
+        exceptions: true
     })
 }
 
 /// Load a Global WordNet XML file without any of the other associated elements
-pub fn load_gwn<P : AsRef<Path>>(path : P) -> Result<WordNet, WordNetLoadError> {
+/// except the links and the OMW definitions and examples (and the gloss tags,
+/// if their directory is given)
+pub fn load_gwn<P : AsRef<Path>>(path : P, glosstags : Option<&str>)
+        -> Result<WordNet, WordNetLoadError> {
     load(path, &LoadConfiguration {
         tabs: false,
-        glosstags: false,
+        glosstags,
         omwn: false,
-        links : false
+        omwn_definitions: true,
+        links : true,
+        exceptions: false
     })
 }
 
 /// Load the English WordNet
-pub fn load_enwn<P : AsRef<Path>>(path : P) -> Result<WordNet, WordNetLoadError> {
+pub fn load_enwn<P : AsRef<Path>>(path : P, glosstags : Option<&str>)
+        -> Result<WordNet, WordNetLoadError> {
     load(path, &LoadConfiguration {
         tabs: false,
-        glosstags: false,
+        glosstags,
         omwn: false,
-        links: false
+        omwn_definitions: false,
+        links: false,
+        exceptions: true
     })
 }
 
@@ -80,19 +101,35 @@ pub fn load_enwn<P : AsRef<Path>>(path : P) -> Result<WordNet, WordNetLoadError>
 fn load<P : AsRef<Path>>(path : P, 
                                  config : &LoadConfiguration) -> Result<WordNet, WordNetLoadError> {
     let mut wordnet = WordNetBuilder::new()?;
+    let mut folding = default_folding();
+    if Path::new(FOLDING_FILE).exists() {
+        folding.extend(read_folding(FOLDING_FILE)?);
+    }
+    wordnet.set_folding(folding)?;
     load_xml(path, &mut wordnet)?;
     if config.tabs {
         build_tabs(&mut wordnet)?;
     }
-    if config.glosstags {
-        build_glosstags(&mut wordnet)?;
+    if let Some(dir) = config.glosstags {
+        build_glosstags(&mut wordnet, dir)?;
     }
-    if config.omwn {
-        build_omwn(&mut wordnet)?;
+    if config.omwn || config.omwn_definitions {
+        // The OMW files are keyed by WordNet 3.0 offsets, which are otherwise
+        // only mapped along with the other schemes
+        if !config.tabs {
+            if let Some(path) = scheme_path("pwn30")? {
+                build_tab(&path, "pwn30", &mut wordnet)?;
+            }
+        }
+        build_omwn(&mut wordnet, config.omwn)?;
     }
     if config.links {
         load_links(&mut wordnet)?;
     }
+    load_link_sources(&mut wordnet)?;
+    if config.exceptions {
+        build_exceptions(&mut wordnet)?;
+    }
     wordnet.finalize()
 }
 
@@ -432,12 +469,15 @@ fn load_xml<P : AsRef<Path>>(path : P,
                             lemmas: entries,
                             id: ssid,
                             ili: ili,
+                            language: language.clone(),
                             pos: PartOfSpeech::from_str(&pos)?,
                             subject: subject,
                             relations: rels,
                             old_keys: HashMap::new(),
                             gloss: None,
                             foreign: HashMap::new(),
+                            foreign_definitions: HashMap::new(),
+                            foreign_examples: HashMap::new(),
                             links: Vec::new()
                         });
                         
@@ -520,48 +560,60 @@ fn build_tab<P : AsRef<Path>>(file : P,
 //}
 
 fn build_tabs(wordnet : &mut WordNetBuilder) -> Result<(),WordNetLoadError> {
-    for tab in ["pwn15", "pwn16", "pwn17", "pwn171", "pwn20",
-                "pwn21", "pwn30"].iter() {
-        eprintln!("Loading Tab {}", tab);
-        let path = format!("data/ili-map-{}.tab", tab);
-        build_tab(path, tab, wordnet)?;
+    for scheme in configured_schemes()? {
+        eprintln!("Loading Tab {}", scheme.name);
+        build_tab(&scheme.path, &scheme.name, wordnet)?;
+        wordnet.add_scheme(&scheme)?;
     }
     Ok(())
 }
 
-fn build_omwn(wordnet : &mut WordNetBuilder) -> Result<(), WordNetLoadError> {
-    for lang in ["als","arb","bul","cmn","qcn","ell","fas","fin","fra",
-                 "heb","hrv","isl","ita","jpn","cat","eus","glg","spa",
-                 "ind","zsm","nld","nno","nob","pol","por","ron",
-                 "slk","lit","slv","swe","tha"].iter() {
-        eprintln!("Loading OMWN {}", lang);
-        let project = match *lang {
-            "cmn" => "cow",
-            "qcn" => "cwn",
-            "ind" => "msa",
-            "zsm" => "msa",
-            "cat" => "mcr",
-            "eus" => "mcr",
-            "glg" => "mcr",
-            "spa" => "mcr",
-            "nno" => "nor",
-            "nob" => "nor",
-            "lit" => "slk",
-            x => x
-        };
-       let omwn = load_omwn(format!("data/wns/{}/wn-data-{}.tab", project, lang),
-            &wordnet)?;
-       for (key, values) in omwn.iter() {
-           if let Some(mut s2) = wordnet.get_synset(&key)? {
-               let mut vs = values.clone();
-               vs.dedup();
-               s2.foreign.insert(lang.to_string(), vs);
-               wordnet.update_synset(key.clone(), s2)?;
-           }
-       }
+fn build_exceptions(wordnet : &mut WordNetBuilder) -> Result<(),WordNetLoadError> {
+    for &(pos, path) in EXCEPTION_FILES.iter() {
+        eprintln!("Loading exceptions {}", path);
+        match read_exceptions(path) {
+            Ok(values) => wordnet.set_exceptions(pos, values)?,
+            Err(e) => eprintln!("Failed to load exceptions {}: {}", path, e)
+        }
     }
     Ok(())
+}
 
+fn build_omwn(wordnet : &mut WordNetBuilder, lemmas : bool) -> Result<(), WordNetLoadError> {
+    let languages = if Path::new(OMW_LANGUAGES_FILE).exists() {
+        read_languages(OMW_LANGUAGES_FILE)?
+    } else {
+        default_languages()
+    };
+    for omw in languages {
+        eprintln!("Loading OMWN {}", omw.language);
+        let lang = omw.language.as_str();
+        let omwn = load_omwn(omw.path(), wordnet)?;
+        if lemmas {
+            for (key, values) in omwn.lemmas.iter() {
+                if let Some(mut s2) = wordnet.get_synset(key)? {
+                    s2.foreign.insert(lang.to_string(), values.clone());
+                    wordnet.update_synset(key.clone(), s2)?;
+                }
+            }
+        }
+        for (key, values) in omwn.definitions.iter() {
+            if let Some(mut s2) = wordnet.get_synset(key)? {
+                s2.foreign_definitions.insert(lang.to_string(), values.clone());
+                wordnet.update_synset(key.clone(), s2)?;
+            }
+        }
+        for (key, values) in omwn.examples.iter() {
+            if let Some(mut s2) = wordnet.get_synset(key)? {
+                s2.foreign_examples.insert(lang.to_string(), values.clone());
+                wordnet.update_synset(key.clone(), s2)?;
+            }
+        }
+        if lemmas {
+            wordnet.add_foreign_lemmas(language_code(lang), &omw.project, &omwn.lemmas)?;
+        }
+    }
+    Ok(())
 }
 
 